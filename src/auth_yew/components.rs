@@ -0,0 +1,92 @@
+use yew::{function_component, Children, Html, Properties};
+
+use super::hooks::{use_auth, use_granted_permissions};
+
+#[derive(Properties, PartialEq)]
+pub struct AuthorizedProps {
+    /// Rendered until the session has been checked.
+    #[prop_or_default]
+    pub loading: Html,
+    /// Rendered when nobody is logged in.
+    #[prop_or_default]
+    pub fallback: Html,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Renders its children only for a logged in user.
+#[function_component(Authorized)]
+pub fn authorized(props: &AuthorizedProps) -> Html {
+    let auth = use_auth();
+
+    match (auth.is_loading, auth.is_authenticated) {
+        (true, _) => props.loading.clone(),
+        (false, true) => props.children.iter().collect(),
+        (false, false) => props.fallback.clone(),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct UnauthenticatedProps {
+    /// Rendered until the session has been checked.
+    #[prop_or_default]
+    pub loading: Html,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Renders its children only when nobody is logged in, e.g. a login button.
+#[function_component(Unauthenticated)]
+pub fn unauthenticated(props: &UnauthenticatedProps) -> Html {
+    let auth = use_auth();
+
+    match (auth.is_loading, auth.is_authenticated) {
+        (true, _) => props.loading.clone(),
+        (false, true) => Html::default(),
+        (false, false) => props.children.iter().collect(),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RequirePermissionProps {
+    /// At least one of these has to be granted, unless empty.
+    #[prop_or_default]
+    pub any_of: Vec<String>,
+    /// Every one of these has to be granted.
+    #[prop_or_default]
+    pub all_of: Vec<String>,
+    /// Rendered until the permissions of the first access token are known.
+    #[prop_or_default]
+    pub loading: Html,
+    /// Rendered when a required permission is missing, also when nobody is logged in.
+    #[prop_or_default]
+    pub fallback: Html,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Renders its children only if the access token for the configured audience grants the required permissions.
+/// Re-renders whenever the token, and with it the permissions, changes.
+///
+/// ```ignore
+/// html! {
+///     <RequirePermission any_of={vec!["read:orders".to_string(), "admin".to_string()]} fallback={html! { "Forbidden" }}>
+///         <Orders />
+///     </RequirePermission>
+/// }
+/// ```
+#[function_component(RequirePermission)]
+pub fn require_permission(props: &RequirePermissionProps) -> Html {
+    let granted = match use_granted_permissions() {
+        Some(granted) => granted,
+        None => return props.loading.clone(),
+    };
+
+    let any = props.any_of.is_empty() || props.any_of.iter().any(|permission| granted.contains(permission));
+    let all = props.all_of.iter().all(|permission| granted.contains(permission));
+    if any && all {
+        props.children.iter().collect()
+    } else {
+        props.fallback.clone()
+    }
+}
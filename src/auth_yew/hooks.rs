@@ -0,0 +1,468 @@
+//! Hooks for function components. They take the [`Auth0Service`] from the surrounding [`crate::Auth0Provider`] or
+//! [`crate::Auth0ServiceProvider`], or from a plain `<ContextProvider<Auth0Service> context={service}>`, and re-render
+//! their component after every session change in this or another tab.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use wasm_bindgen_futures::spawn_local;
+use yew::{use_context, use_effect_with_deps, use_state, Callback, UseStateHandle};
+
+use crate::{
+    browser, refresh::refresh_delay, Auth0Error, Auth0Service, AuthChannel, AuthEvent, AuthLogoutOptions,
+    RedirectLoginResult, TokenOptions, User,
+};
+
+/// How long before the access token expires [`use_access_token`] loads a new one.
+const RELOAD_MARGIN: Duration = Duration::from_secs(60);
+
+thread_local! {
+    /// Hooks to notify of session changes made in this tab, the other tabs hear about them through the service's
+    /// channel.
+    static LISTENERS: RefCell<Vec<(usize, Callback<AuthEvent>)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_LISTENER: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Session state of the current user, with the actions that change it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AuthState {
+    /// Set until the session has been checked for the first time.
+    pub is_loading: bool,
+    pub is_authenticated: bool,
+    pub user: Option<User>,
+    /// The error of the last session check or action.
+    pub error: Option<Auth0Error>,
+    pub login_with_redirect: Callback<()>,
+    pub login_with_popup: Callback<()>,
+    pub logout: Callback<Option<AuthLogoutOptions>>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Session {
+    is_loading: bool,
+    is_authenticated: bool,
+    user: Option<User>,
+    error: Option<Auth0Error>,
+}
+
+/// The session checked once by [`crate::Auth0Provider`] and shared by every hook below it, with the access token for
+/// the configured audience and its permissions.
+#[derive(Clone, PartialEq)]
+pub(crate) struct SharedSession {
+    pub session: UseStateHandle<Session>,
+    /// The redirect callback this page was loaded with, an error e.g. if the user denied access.
+    callback: Option<Result<RedirectLoginResult, Auth0Error>>,
+    /// The error of that callback, until the next login or until the app navigates away from where it came back to.
+    callback_error: UseStateHandle<Option<CallbackError>>,
+    token: Option<Option<String>>,
+    permissions: Option<Vec<String>>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct CallbackError {
+    error: Auth0Error,
+    location: String,
+}
+
+impl SharedSession {
+    fn clear_callback_error(&self) {
+        if self.callback_error.is_some() {
+            self.callback_error.set(None);
+        }
+    }
+}
+
+impl Session {
+    fn loading() -> Self {
+        Session {
+            is_loading: true,
+            is_authenticated: false,
+            user: None,
+            error: None,
+        }
+    }
+
+    fn failed(&self, error: Auth0Error) -> Self {
+        Session {
+            error: Some(error),
+            ..self.clone()
+        }
+    }
+}
+
+pub fn use_auth() -> AuthState {
+    let service = use_service();
+    let session = use_session(&service);
+    let shared = use_context::<SharedSession>();
+
+    let login_with_redirect = {
+        let (service, session, shared) = (service.clone(), session.clone(), shared.clone());
+        Callback::from(move |_| {
+            if let Some(shared) = &shared {
+                shared.clear_callback_error();
+            }
+            let (service, session) = (service.clone(), session.clone());
+            spawn_local(async move {
+                if let Err(err) = service.login_with_redirect().await {
+                    session.set(session.failed(err));
+                }
+            });
+        })
+    };
+    let login_with_popup = {
+        let (service, session) = (service.clone(), session.clone());
+        Callback::from(move |_| {
+            if let Some(shared) = &shared {
+                shared.clear_callback_error();
+            }
+            let (service, session) = (service.clone(), session.clone());
+            spawn_local(async move {
+                match service.login_with_popup().await {
+                    Ok(()) => notify(AuthEvent::Login),
+                    Err(err) => session.set(session.failed(err)),
+                }
+            });
+        })
+    };
+    let logout = {
+        let session = session.clone();
+        Callback::from(move |options| match service.logout(options) {
+            Ok(()) => notify(AuthEvent::Logout),
+            Err(err) => session.set(session.failed(err)),
+        })
+    };
+
+    let Session {
+        is_loading,
+        is_authenticated,
+        user,
+        error,
+    } = (*session).clone();
+    AuthState {
+        is_loading,
+        is_authenticated,
+        user,
+        error,
+        login_with_redirect,
+        login_with_popup,
+        logout,
+    }
+}
+
+/// The logged in user, `None` while loading or when logged out.
+pub fn use_user() -> Option<User> {
+    let service = use_service();
+    let session = use_session(&service);
+
+    session.user.clone()
+}
+
+/// An access token for `audience`, or the configured audience if `None`. `None` while loading, when logged out or
+/// when no token can be obtained silently.
+pub fn use_access_token(audience: Option<&str>) -> Option<String> {
+    use_token(audience).flatten()
+}
+
+/// Calls `navigate` with the location a [`crate::ProtectedRoute`] started the login from, once this page has come back
+/// from that login. [`crate::Auth0Provider`] only replaces the address, a router that is already mounted has to be
+/// told with its own history:
+///
+/// ```ignore
+/// let history = use_history().unwrap();
+/// use_return_to(Callback::from(move |location: String| {
+///     if let Some(route) = AppRoute::recognize(&location) {
+///         history.replace(route);
+///     }
+/// }));
+/// ```
+pub fn use_return_to(navigate: Callback<String>) {
+    let return_to = use_context::<SharedSession>()
+        .and_then(|shared| shared.callback)
+        .and_then(Result::ok)
+        .and_then(|result| super::protected::return_location(&result));
+
+    use_effect_with_deps(
+        move |return_to| {
+            if let Some(return_to) = return_to {
+                navigate.emit(return_to.clone());
+            }
+            || ()
+        },
+        return_to,
+    );
+}
+
+/// Whether the `permissions` claim of the access token for the configured audience contains `permission`.
+pub fn use_permission(permission: &str) -> bool {
+    use_granted_permissions().is_some_and(|granted| granted.iter().any(|granted| granted == permission))
+}
+
+/// The `permissions` claim of the access token for the configured audience, `None` while loading.
+pub(crate) fn use_granted_permissions() -> Option<Vec<String>> {
+    let service = use_service();
+    let shared = use_context::<SharedSession>();
+    let token = use_token(None);
+    let permissions = use_loaded_permissions(&service, token, shared.is_none());
+
+    match shared {
+        Some(shared) => shared.permissions,
+        None => permissions,
+    }
+}
+
+pub(crate) fn use_service() -> Auth0Service {
+    use_context::<Auth0Service>().expect("Auth0 hooks need an Auth0Service in the context")
+}
+
+/// Like [`use_access_token`], but `None` only while loading. The token for the configured audience is the one shared
+/// by the provider.
+fn use_token(audience: Option<&str>) -> Option<Option<String>> {
+    let service = use_service();
+    let shared = use_context::<SharedSession>().filter(|_| audience.is_none());
+    let token = use_loaded_token(&service, audience.map(str::to_string), shared.is_none());
+
+    match shared {
+        Some(shared) => shared.token,
+        None => token,
+    }
+}
+
+/// Checks the session and loads the access token for the configured audience and its permissions, once for every
+/// hook below [`crate::Auth0Provider`]. The token is only requested while somebody is logged in.
+pub(crate) fn use_shared_session(
+    service: &Auth0Service,
+    callback: Option<Result<RedirectLoginResult, Auth0Error>>,
+) -> SharedSession {
+    let session = use_session(service);
+    let token = use_loaded_token(service, None, !session.is_loading && session.is_authenticated);
+    let token = match (session.is_loading, session.is_authenticated) {
+        (true, _) => None,
+        (false, false) => Some(None),
+        (false, true) => token,
+    };
+    let permissions = use_loaded_permissions(service, token.clone(), true);
+
+    let failed = |callback: &Option<Result<RedirectLoginResult, Auth0Error>>| {
+        let error = callback.clone()?.err()?;
+        let location = super::protected::current_location().unwrap_or_default();
+        Some(CallbackError { error, location })
+    };
+    let callback_error = use_state(|| failed(&callback));
+    {
+        let callback_error = callback_error.clone();
+        use_effect_with_deps(
+            move |callback| {
+                let error = failed(callback);
+                if *callback_error != error {
+                    callback_error.set(error);
+                }
+                || ()
+            },
+            callback.clone(),
+        );
+    }
+
+    SharedSession {
+        session,
+        callback,
+        callback_error,
+        token,
+        permissions,
+    }
+}
+
+/// The access token for `audience` while `enabled`, loaded again after every session change and shortly before it
+/// expires.
+fn use_loaded_token(service: &Auth0Service, audience: Option<String>, enabled: bool) -> Option<Option<String>> {
+    let token = use_state(|| None);
+
+    {
+        let token = token.clone();
+        use_effect_with_deps(
+            move |(service, audience, enabled)| {
+                // Bumped by every load and on cleanup, so only the latest load keeps reloading.
+                let generation = Rc::new(Cell::new(0_usize));
+                let subscription = if *enabled {
+                    let load = {
+                        let (service, audience, generation) = (service.clone(), audience.clone(), generation.clone());
+                        move |_| {
+                            let (service, token, generation) = (service.clone(), token.clone(), generation.clone());
+                            let options = audience.clone().map(TokenOptions::audience);
+                            let current = generation.get() + 1;
+                            generation.set(current);
+                            spawn_local(async move {
+                                while generation.get() == current {
+                                    let response = service.get_token_response(options.clone()).await;
+                                    if generation.get() != current {
+                                        break;
+                                    }
+                                    let delay = response
+                                        .as_ref()
+                                        .ok()
+                                        .map(|response| refresh_delay(response, RELOAD_MARGIN));
+                                    token.set(Some(response.ok().map(|response| response.access_token)));
+
+                                    let millis = match delay {
+                                        Some(delay) => delay.as_millis().min(i32::MAX as u128) as i32,
+                                        None => break,
+                                    };
+                                    if browser::sleep(millis).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            });
+                        }
+                    };
+                    load(AuthEvent::Login);
+                    Some(Subscription::new(service, load))
+                } else {
+                    token.set(None);
+                    None
+                };
+                move || {
+                    generation.set(generation.get() + 1);
+                    drop(subscription)
+                }
+            },
+            (service.clone(), audience, enabled),
+        );
+    }
+
+    (*token).clone()
+}
+
+/// The `permissions` claim of `token` while `enabled`, see [`super::token_permissions`].
+fn use_loaded_permissions(service: &Auth0Service, token: Option<Option<String>>, enabled: bool) -> Option<Vec<String>> {
+    let permissions = use_state(|| None);
+
+    {
+        let permissions = permissions.clone();
+        use_effect_with_deps(
+            move |(service, token, enabled)| {
+                match token.clone() {
+                    _ if !enabled => {}
+                    Some(Some(token)) => {
+                        let service = service.clone();
+                        spawn_local(async move {
+                            permissions.set(Some(super::token_permissions(&service, &token).await))
+                        });
+                    }
+                    Some(None) => permissions.set(Some(Vec::new())),
+                    None => permissions.set(None),
+                }
+                || ()
+            },
+            (service.clone(), token, enabled),
+        );
+    }
+
+    (*permissions).clone()
+}
+
+/// The session shared by the provider, or else checked on mount and again after every session change.
+pub(crate) fn use_session(service: &Auth0Service) -> UseStateHandle<Session> {
+    let shared = use_context::<SharedSession>();
+    let session = use_state(Session::loading);
+
+    {
+        let session = session.clone();
+        use_effect_with_deps(
+            move |(service, is_shared)| {
+                let subscription = (!is_shared).then(|| {
+                    let load = {
+                        let service = service.clone();
+                        move |_| {
+                            let (service, session) = (service.clone(), session.clone());
+                            spawn_local(async move { session.set(check_session(&service).await) });
+                        }
+                    };
+                    load(AuthEvent::Login);
+                    Subscription::new(service, load)
+                });
+                move || drop(subscription)
+            },
+            (service.clone(), shared.is_some()),
+        );
+    }
+
+    shared.map(|shared| shared.session).unwrap_or(session)
+}
+
+/// The error of the redirect callback handled by [`crate::Auth0Provider`] when this page loaded. Cleared by the next
+/// login and once the app navigates away from the location the callback came back to.
+pub(crate) fn use_callback_error() -> Option<Auth0Error> {
+    let shared = use_context::<SharedSession>();
+    let failed = shared.as_ref().and_then(|shared| (*shared.callback_error).clone());
+    let navigated = failed
+        .as_ref()
+        .is_some_and(|failed| super::protected::current_location().ok().as_ref() != Some(&failed.location));
+
+    use_effect_with_deps(
+        move |navigated| {
+            if let (true, Some(shared)) = (*navigated, &shared) {
+                shared.clear_callback_error();
+            }
+            || ()
+        },
+        navigated,
+    );
+
+    failed.filter(|_| !navigated).map(|failed| failed.error)
+}
+
+async fn check_session(service: &Auth0Service) -> Session {
+    let user = match service.is_authenticated().await {
+        Ok(true) => service.user().await,
+        Ok(false) => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    match user {
+        Ok(user) => Session {
+            is_loading: false,
+            is_authenticated: user.is_some(),
+            user,
+            error: None,
+        },
+        Err(err) => Session {
+            is_loading: false,
+            ..Session::loading().failed(err)
+        },
+    }
+}
+
+/// Tells the hooks of this tab about a session change made here.
+pub(crate) fn notify(event: AuthEvent) {
+    let listeners: Vec<_> = LISTENERS.with(|listeners| listeners.borrow().iter().map(|(_, l)| l.clone()).collect());
+    for listener in listeners {
+        listener.emit(event.clone());
+    }
+}
+
+/// Calls `on_event` for session changes in this and other tabs until dropped.
+struct Subscription {
+    id: usize,
+    _channel: Option<AuthChannel>,
+}
+
+impl Subscription {
+    fn new(service: &Auth0Service, on_event: impl Fn(AuthEvent) + 'static) -> Self {
+        let on_event = Callback::from(on_event);
+        let id = NEXT_LISTENER.with(|next| next.replace(next.get() + 1));
+        LISTENERS.with(|listeners| listeners.borrow_mut().push((id, on_event.clone())));
+
+        Subscription {
+            id,
+            _channel: service.on_auth_event(move |event| on_event.emit(event)).ok(),
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        LISTENERS.with(|listeners| listeners.borrow_mut().retain(|(id, _)| *id != self.id));
+    }
+}
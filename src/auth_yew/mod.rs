@@ -1,122 +1,103 @@
-mod model;
+mod components;
+pub mod hooks;
+#[cfg(feature = "auth0-yew-agent")]
 pub mod permissions;
+mod protected;
+mod provider;
 
-use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
-use once_cell::sync::OnceCell;
-
-use std::convert::TryFrom;
+use std::future::Future;
 
-use lazy_static::lazy_static;
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
 use yew::Callback;
 
-use crate::{
-    Auth0Client, Auth0ClientOptions, LogoutOptions, GetTokenSilentlyOptions,
+pub use self::{
+    components::{
+        Authorized, AuthorizedProps, RequirePermission, RequirePermissionProps, Unauthenticated, UnauthenticatedProps,
+    },
+    protected::{ProtectedRoute, ProtectedRouteProps},
+    provider::{Auth0Provider, Auth0ProviderProps, Auth0ServiceProvider, Auth0ServiceProviderProps},
 };
 
-pub use self::model::{Claim, ConfigOptions, User, AuthLogoutOptions, TokenOptions};
+use crate::{Auth0Error, Auth0Service, RedirectLoginResult, TokenOptions, User};
 
-pub static AUTH0_DOMAIN: OnceCell<String> = OnceCell::new();
-pub static AUTH0_CLIENT_ID: OnceCell<String> = OnceCell::new();
-pub static AUTH0_REDIRECT_URI: OnceCell<String> = OnceCell::new();
-pub static AUTH0_USE_REFRESH_TOKENS: OnceCell<bool> = OnceCell::new();
-pub static AUTH0_CACHE_LOCATION: OnceCell<String> = OnceCell::new();
+/// Callback based adapter over the async [`Auth0Service`] API, for use from Yew components and agents.
+pub struct Auth0Callbacks<'a>(&'a Auth0Service);
 
-lazy_static! {
-    pub static ref AUTH0_SERVICE: Auth0Service = Auth0Service::new();
+impl Auth0Service {
+    pub fn callbacks(&self) -> Auth0Callbacks<'_> {
+        Auth0Callbacks(self)
+    }
 }
 
-pub struct Auth0Service(pub Auth0Client);
+impl Auth0Callbacks<'_> {
+    pub fn login_with_redirect(&self, callback: Callback<Result<(), Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.login_with_redirect().await });
+    }
 
-impl Auth0Service {
-    pub fn new() -> Self {
-        let options = ConfigOptions {
-            domain: AUTH0_DOMAIN.get().expect("AUTH0_DOMAIN not set").to_string(),
-            client_id: AUTH0_CLIENT_ID.get().expect("AUTH0_CLIENT_ID not set").to_string(),
-            redirect_uri: AUTH0_REDIRECT_URI.get().expect("AUTH0_REDIRECT_URI not set").to_string(),
-            useRefreshTokens: *AUTH0_USE_REFRESH_TOKENS.get().expect("AUTH0_USE_REFRESH_TOKENS not set"),
-            cacheLocation: AUTH0_CACHE_LOCATION.get().expect("AUTH0_CACHE_LOCATION not set").to_string(),
-            audience: "https://vendenic.com".to_string(),
-        };
-
-        Auth0Service(Auth0Client::new(
-            Auth0ClientOptions::try_from(JsValue::from_serde(&options).unwrap()).unwrap(),
-        ))
+    pub fn login_with_popup(&self, callback: Callback<Result<(), Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.login_with_popup().await });
     }
 
-    pub fn login_with_redirect() {
-        spawn_local(async move {
-            AUTH0_SERVICE.0.login_with_redirect(None).await;
-        });
+    pub fn handle_redirect_callback(&self, callback: Callback<Result<RedirectLoginResult, Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.handle_redirect_callback().await });
     }
 
-    pub fn login_with_popup() {
-        spawn_local(async move {
-            AUTH0_SERVICE.0.login_with_popup(None, None).await;
-        });
+    pub fn process_redirect_callback(&self, callback: Callback<Option<Result<RedirectLoginResult, Auth0Error>>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.process_redirect_callback().await });
     }
 
-    pub fn handle_redirect_callback(callback: Callback<Result<JsValue, JsValue>>) {
-        spawn_local(async move {
-            let result = AUTH0_SERVICE.0.handle_redirect_callback(None).await;
-            callback.emit(result);
-        });
+    pub fn check_session(&self, callback: Callback<Result<(), Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.check_session().await });
     }
 
-    pub fn get_user(callback: Callback<Option<User>>) {
-        spawn_local(async move {
-            let user_js = AUTH0_SERVICE.0.get_user(None).await;
-            match JsValue::into_serde(&user_js) {
-                Ok(user) => {
-                    callback.emit(Some(user));
-                }
-                Err(_) => {
-                    callback.emit(None);
-                }
-            }
-        });
+    pub fn user(&self, callback: Callback<Result<Option<User>, Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.user().await });
     }
 
-    pub async fn get_access_token() -> Result<String, JsValue> {
-        let options = TokenOptions {
-            audience: "https://vendenic.com".to_string(),
-        };
-
-        let access_token = match AUTH0_SERVICE.0.get_token_silently(
-            Some(GetTokenSilentlyOptions::try_from(JsValue::from_serde(&options).unwrap()).unwrap())
-        ).await {
-            Ok(token) => token,
-            Err(err) => {
-                return Err(err);
-            },
-        };
-
-        match JsValue::into_serde::<String>(&access_token) {
-            Ok(token) => {
-               return Ok(token);
-            }
-            Err(_err) => {
-               return Err(JsValue::from_str("get token err: failed to parse JsValue"));
-            }
-        }
+    pub fn is_authenticated(&self, callback: Callback<Result<bool, Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.is_authenticated().await });
     }
 
-    pub fn is_authenticated(callback: Callback<bool>) {
-        spawn_local(async move {
-            let result = AUTH0_SERVICE.0.is_authenticated().await.as_bool().unwrap();
-            callback.emit(result);
-        });
+    pub fn get_access_token(&self, options: Option<TokenOptions>, callback: Callback<Result<String, Auth0Error>>) {
+        let service = self.0.clone();
+        emit(callback, async move { service.get_access_token(options).await });
     }
+}
 
-    pub fn logout() {
-        spawn_local(async move {
-            let logout_options = AuthLogoutOptions {
-                returnTo: AUTH0_REDIRECT_URI.get().expect("AUTH0_REDIRECT_URI not set").to_string(),
-            };
+fn emit<T: 'static>(callback: Callback<T>, future: impl Future<Output = T> + 'static) {
+    spawn_local(async move {
+        callback.emit(future.await);
+    });
+}
 
-            AUTH0_SERVICE.0.logout(Some(
-                LogoutOptions::try_from(JsValue::from_serde(&logout_options).unwrap()).unwrap(),
-            ));
-        });
-    }
+#[derive(Deserialize)]
+struct Permissions {
+    #[serde(default)]
+    permissions: Vec<String>,
+}
+
+/// The `permissions` claim of an access token. A token we can't decode grants nothing.
+#[cfg(not(feature = "jwks"))]
+async fn token_permissions(_: &Auth0Service, token: &str) -> Vec<String> {
+    crate::jwt::decode_claims::<Permissions>(token)
+        .map(|claims| claims.permissions)
+        .unwrap_or_default()
+}
+
+/// With `jwks` enabled only a token that verifies against the configured audience grants permissions.
+#[cfg(feature = "jwks")]
+async fn token_permissions(service: &Auth0Service, token: &str) -> Vec<String> {
+    let verified = match service.config().audience.as_deref() {
+        Some(audience) => service.verifier().verify_access_token::<Permissions>(token, audience).await.ok(),
+        None => None,
+    };
+    verified.map(|claims| claims.permissions).unwrap_or_default()
 }
@@ -1,12 +1,9 @@
 use std::{collections::HashSet, sync::Mutex};
-use crate::{Auth0Service, User};
-use wasm_bindgen::prelude::*;
+use crate::{
+    Auth0Error, Auth0Service, AuthChannel, AuthEvent, RefreshEvent, RefreshOptions, RefreshScheduler, TokenResponse, User,
+};
 
 use lazy_static::{__Deref, lazy_static};
-use wasm_bindgen::JsValue;
-use serde::Deserialize;
-use gloo_timers::callback::{Timeout, Interval};
-use wasm_bindgen_futures::spawn_local;
 use yew_agent::*;
 
 lazy_static! {
@@ -33,24 +30,35 @@ impl PermissionsService {
 }
 
 pub enum Msg {
-    GetAccessToken(Result<String, JsValue>),
-    UserData(Option<User>),
-    CheckSession,
-    CheckSessionResponse(bool),
+    Refresh(RefreshEvent),
+    /// A session change in another tab.
+    Remote(AuthEvent),
+    Token(Result<TokenResponse, Auth0Error>),
+    UserData(Option<Box<User>>),
+    Permissions(Vec<String>),
 }
 
 pub enum Input {
-    Start,
+    Start(Auth0Service),
+    /// Like `Start`, with a custom refresh margin and backoff.
+    StartWith(Auth0Service, RefreshOptions),
 }
 
 pub enum Output {
     Initialized,
+    /// Forwarded from the [`RefreshScheduler`] that keeps `ACCESS_TOKEN` fresh.
+    Refresh(RefreshEvent),
+    /// A session change in another tab, after the shared state has been updated.
+    Synced(AuthEvent),
 }
 
 pub struct PermissionsAgent {
     subscribers: HashSet<HandlerId>,
     link: AgentLink<Self>,
-    timeout: Timeout,
+    scheduler: Option<RefreshScheduler>,
+    channel: Option<AuthChannel>,
+    service: Option<Auth0Service>,
+    options: RefreshOptions,
 }
 
 impl Agent for PermissionsAgent {
@@ -63,58 +71,70 @@ impl Agent for PermissionsAgent {
     type Output = Output;
 
     fn create(link: AgentLink<Self>) -> Self {
-
-        let callback = link.callback(|()| Msg::CheckSession);
-        // Idea: maybe instead of timer, check session only on actions
-        let timeout = Timeout::new(500, move || {
-            callback.emit(())
-        });
-
         Self {
             subscribers: HashSet::new(),
             link,
-            timeout
+            scheduler: None,
+            channel: None,
+            service: None,
+            options: RefreshOptions::default(),
         }
     }
 
     fn update(&mut self, msg: Self::Message) {
         match msg {
-            Msg::GetAccessToken(result) => {
-                match result {
-                    Ok(token) => {
-                        *ACCESS_TOKEN.lock().unwrap() = Some(token.clone());
-                        self.parse_permissions(token);
+            Msg::Refresh(event) => {
+                match &event {
+                    RefreshEvent::Refreshed(response) => self.set_access_token(&response.access_token),
+                    // The current token stays usable until the retry.
+                    RefreshEvent::Failed { .. } => {}
+                    RefreshEvent::LoginRequired(_) => self.clear(),
+                }
+                for id in &self.subscribers {
+                    self.link.respond(*id, Output::Refresh(event.clone()));
+                }
+            }
+            Msg::Remote(event) => {
+                match (&event, self.service.clone()) {
+                    (AuthEvent::Logout, _) | (_, None) => self.clear(),
+                    // The scheduler stopped when this tab needed a login, the session is back now.
+                    (AuthEvent::Login | AuthEvent::TokenRefreshed, Some(service)) if !self.is_refreshing() => {
+                        self.fetch_user(&service);
+                        self.schedule(service);
                     }
-                    Err(_err) => {
-                        *ACCESS_TOKEN.lock().unwrap() = None;
-                        for id in &self.subscribers {
-                            self.link.respond(*id, Output::Initialized);
-                        }
+                    (_, Some(service)) => {
+                        self.fetch_user(&service);
+                        self.link.send_future(async move { Msg::Token(service.get_token_response(None).await) });
                     }
                 }
+                for id in &self.subscribers {
+                    self.link.respond(*id, Output::Synced(event.clone()));
+                }
             }
+            Msg::Token(Ok(response)) => self.set_access_token(&response.access_token),
+            Msg::Token(Err(err)) if err.requires_login() => self.clear(),
+            Msg::Token(Err(_)) => {}
             Msg::UserData(user) => {
-                *USER.lock().unwrap() = user;
+                *USER.lock().unwrap() = user.map(|user| *user);
             }
-            Msg::CheckSession => {
-                Auth0Service::is_authenticated(self.link.callback(Msg::CheckSessionResponse));
-            }
-            Msg::CheckSessionResponse(_is_authenticated) => {
-                // Idea: maybe instead of timer, check session only on actions
-                let callback = self.link.callback(|()| Msg::CheckSession);
-                self.timeout = Timeout::new(5000, move || {
-                    callback.emit(())
-                });
+            Msg::Permissions(permissions) => {
+                let changed = *PERMISSIONS.lock().unwrap() != permissions;
+                *PERMISSIONS.lock().unwrap() = permissions;
+                if let (true, Some(service)) = (changed, &self.service) {
+                    let _ = service.broadcast(AuthEvent::PermissionsChanged);
+                }
+
+                for id in &self.subscribers {
+                    self.link.respond(*id, Output::Initialized);
+                }
             }
         }
     }
 
     fn handle_input(&mut self, msg: Self::Input, _: HandlerId) {
         match msg {
-            Input::Start => {
-                Auth0Service::get_user(self.link.callback(Msg::UserData));
-                self.fetch_permissions();
-            }
+            Input::Start(service) => self.start(service, RefreshOptions::default()),
+            Input::StartWith(service, options) => self.start(service, options),
         }
     }
 
@@ -127,165 +147,57 @@ impl Agent for PermissionsAgent {
     }
 }
 
-
-#[derive(Deserialize, Clone, PartialEq, Debug)]
-struct Permissions {
-    permissions: Vec<String>,
-}
-
 impl PermissionsAgent {
-    fn fetch_permissions(&self) {
-        let callback = self.link.callback(move |result: Result<String, JsValue>| Msg::GetAccessToken(result.clone()));
-        spawn_local(async move {
-            let result = Auth0Service::get_access_token().await;
-            callback.emit(result);
-        });
-    }
-
-    fn parse_permissions(&self, token: String) {
-        let claims = parse_jwt(&token);
-
-        let permissions: Permissions = JsValue::into_serde(&claims).unwrap();
-
-        *PERMISSIONS.lock().unwrap() = permissions.permissions;
-
-        for id in &self.subscribers {
-            self.link.respond(*id, Output::Initialized);
-        }
-    }
-}
-
-#[wasm_bindgen(inline_js = r#"
-(function (factory) {
-    typeof define === 'function' && define.amd ? define(factory) :
-    factory();
-}((function () { 'use strict';
+    fn start(&mut self, service: Auth0Service, options: RefreshOptions) {
+        self.fetch_user(&service);
 
-    /**
-     * The code was extracted from:
-     * https://github.com/davidchambers/Base64.js
-     */
-
-    var chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
-
-    function InvalidCharacterError(message) {
-        this.message = message;
+        self.options = options;
+        self.schedule(service.clone());
+        let callback = self.link.callback(Msg::Remote);
+        self.channel = service.on_auth_event(move |event| callback.emit(event)).ok();
+        self.service = Some(service);
     }
 
-    InvalidCharacterError.prototype = new Error();
-    InvalidCharacterError.prototype.name = "InvalidCharacterError";
-
-    function polyfill(input) {
-        var str = String(input).replace(/=+$/, "");
-        if (str.length % 4 == 1) {
-            throw new InvalidCharacterError(
-                "'atob' failed: The string to be decoded is not correctly encoded."
-            );
-        }
-        for (
-            // initialize result and counters
-            var bc = 0, bs, buffer, idx = 0, output = "";
-            // get next character
-            (buffer = str.charAt(idx++));
-            // character found in table? initialize bit storage and add its ascii value;
-            ~buffer &&
-            ((bs = bc % 4 ? bs * 64 + buffer : buffer),
-                // and if not first of each 4 characters,
-                // convert the first 8 bits to one ascii character
-                bc++ % 4) ?
-            (output += String.fromCharCode(255 & (bs >> ((-2 * bc) & 6)))) :
-            0
-        ) {
-            // try to find character in table (0-63, not found => -1)
-            buffer = chars.indexOf(buffer);
-        }
-        return output;
+    /// Starts a new scheduler, which fetches a token right away.
+    fn schedule(&mut self, service: Auth0Service) {
+        let callback = self.link.callback(Msg::Refresh);
+        self.scheduler = Some(RefreshScheduler::start(service, self.options.clone(), move |event| {
+            callback.emit(event)
+        }));
     }
 
-    var atob = (typeof window !== "undefined" &&
-        window.atob &&
-        window.atob.bind(window)) ||
-    polyfill;
-
-    function b64DecodeUnicode(str) {
-        return decodeURIComponent(
-            atob(str).replace(/(.)/g, function(m, p) {
-                var code = p.charCodeAt(0).toString(16).toUpperCase();
-                if (code.length < 2) {
-                    code = "0" + code;
-                }
-                return "%" + code;
-            })
-        );
+    fn is_refreshing(&self) -> bool {
+        self.scheduler.as_ref().is_some_and(RefreshScheduler::is_running)
     }
 
-    function base64_url_decode(str) {
-        var output = str.replace(/-/g, "+").replace(/_/g, "/");
-        switch (output.length % 4) {
-            case 0:
-                break;
-            case 2:
-                output += "==";
-                break;
-            case 3:
-                output += "=";
-                break;
-            default:
-                throw "Illegal base64url string!";
-        }
-
-        try {
-            return b64DecodeUnicode(output);
-        } catch (err) {
-            return atob(output);
-        }
+    fn fetch_user(&self, service: &Auth0Service) {
+        service.callbacks().user(self.link.callback(|result: Result<Option<User>, Auth0Error>| {
+            Msg::UserData(result.ok().flatten().map(Box::new))
+        }));
     }
 
-    function InvalidTokenError(message) {
-        this.message = message;
+    fn set_access_token(&self, token: &str) {
+        *ACCESS_TOKEN.lock().unwrap() = Some(token.to_string());
+        self.parse_permissions(token.to_string());
     }
 
-    InvalidTokenError.prototype = new Error();
-    InvalidTokenError.prototype.name = "InvalidTokenError";
-
-    function jwtDecode(token, options) {
-        if (typeof token !== "string") {
-            throw new InvalidTokenError("Invalid token specified");
-        }
-
-        options = options || {};
-        var pos = options.header === true ? 0 : 1;
-        try {
-            return JSON.parse(base64_url_decode(token.split(".")[pos]));
-        } catch (e) {
-            throw new InvalidTokenError("Invalid token specified: " + e.message);
+    /// Drops the user, token and permissions, e.g. after a logout in this or another tab.
+    fn clear(&self) {
+        *USER.lock().unwrap() = None;
+        *ACCESS_TOKEN.lock().unwrap() = None;
+        PERMISSIONS.lock().unwrap().clear();
+        for id in &self.subscribers {
+            self.link.respond(*id, Output::Initialized);
         }
     }
 
-    /*
-     * Expose the function on the window object
-     */
+    fn parse_permissions(&self, token: String) {
+        let service = match &self.service {
+            Some(service) => service.clone(),
+            None => return self.link.send_message(Msg::Permissions(Vec::new())),
+        };
 
-    //use amd or just through the window object.
-    if (window) {
-        if (typeof window.define == "function" && window.define.amd) {
-            window.define("jwt_decode", function() {
-                return jwtDecode;
-            });
-        } else if (window) {
-            window.jwt_decode = jwtDecode;
-        }
+        self.link
+            .send_future(async move { Msg::Permissions(super::token_permissions(&service, &token).await) });
     }
-
-})));
-//# sourceMappingURL=jwt-decode.js.map
-
-export function parse_jwt (token) {
-    var decoded = jwt_decode(token);
-    console.log(decoded);
-    return decoded;
-};"#)]
-extern "C" {
-    fn parse_jwt(token: &str) -> JsValue;
 }
-
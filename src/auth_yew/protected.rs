@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use yew::{function_component, use_effect_with_deps, Callback, Children, Html, Properties};
+
+use super::hooks::{use_auth, use_callback_error, use_granted_permissions, use_service};
+use crate::{browser, Auth0Error, RedirectLoginResult};
+
+/// The `appState` of a login started by a [`ProtectedRoute`].
+#[derive(Serialize, Deserialize)]
+struct ReturnTo {
+    #[serde(rename = "returnTo")]
+    return_to: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ProtectedRouteProps {
+    /// Permissions the access token must all grant, see [`crate::hooks::use_permission`].
+    #[prop_or_default]
+    pub permissions: Vec<String>,
+    /// Rendered while the session is checked and while the browser is redirected to the login.
+    #[prop_or_default]
+    pub loading: Html,
+    /// Rendered when the user is logged in but lacks a permission.
+    #[prop_or_default]
+    pub forbidden: Html,
+    /// Rendered instead of sending the user to the login again when the login this page came back from failed,
+    /// e.g. because the user denied access. Stays until the user logs in with [`crate::hooks::use_auth`] or the app
+    /// navigates elsewhere.
+    #[prop_or_default]
+    pub failed: Html,
+    /// Called if the login redirect fails, or with the error of the failed login this page came back from.
+    #[prop_or_default]
+    pub on_error: Callback<Auth0Error>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Renders its children only for a logged in user with all of `permissions`. Anyone else is sent to the login,
+/// and [`crate::Auth0Provider`] brings them back to the current location afterwards. If that login failed, `failed`
+/// is rendered instead of starting another one.
+///
+/// Works with the `render` function of yew-router's `Switch`:
+///
+/// ```ignore
+/// fn switch(route: &AppRoute) -> Html {
+///     match route {
+///         AppRoute::Orders => html! {
+///             <ProtectedRoute permissions={vec!["read:orders".to_string()]}>
+///                 <Orders />
+///             </ProtectedRoute>
+///         },
+///         AppRoute::Home => html! { <Home /> },
+///     }
+/// }
+/// ```
+///
+/// A router inside the `Auth0Provider` starts at the location restored after the login. A router around it is already
+/// mounted when the login comes back, navigate it there with [`crate::hooks::use_return_to`].
+#[function_component(ProtectedRoute)]
+pub fn protected_route(props: &ProtectedRouteProps) -> Html {
+    let service = use_service();
+    let auth = use_auth();
+    let granted = use_granted_permissions();
+    let callback_error = use_callback_error();
+
+    {
+        let on_error = props.on_error.clone();
+        use_effect_with_deps(
+            move |(service, is_loading, is_authenticated, callback_error)| {
+                if !is_loading && !is_authenticated {
+                    match callback_error.clone() {
+                        // Logging in again would only come back with the same error.
+                        Some(err) => on_error.emit(err),
+                        None => {
+                            let service = service.clone();
+                            spawn_local(async move {
+                                let result = match current_location() {
+                                    Ok(return_to) => match serde_json::to_value(ReturnTo { return_to }) {
+                                        Ok(app_state) => service.login_with_app_state(app_state).await,
+                                        Err(err) => Err(Auth0Error::Deserialize(err.to_string())),
+                                    },
+                                    Err(err) => Err(err),
+                                };
+                                if let Err(err) = result {
+                                    on_error.emit(err);
+                                }
+                            });
+                        }
+                    }
+                }
+                || ()
+            },
+            (service, auth.is_loading, auth.is_authenticated, callback_error.clone()),
+        );
+    }
+
+    if !auth.is_loading && !auth.is_authenticated && callback_error.is_some() {
+        return props.failed.clone();
+    }
+    if auth.is_loading || !auth.is_authenticated {
+        return props.loading.clone();
+    }
+    match granted {
+        _ if props.permissions.is_empty() => children(props),
+        None => props.loading.clone(),
+        Some(granted) if props.permissions.iter().all(|permission| granted.contains(permission)) => children(props),
+        Some(_) => props.forbidden.clone(),
+    }
+}
+
+fn children(props: &ProtectedRouteProps) -> Html {
+    props.children.iter().collect()
+}
+
+/// Path, query and fragment of the current location, which covers both browser and hash routing.
+pub(crate) fn current_location() -> Result<String, Auth0Error> {
+    let location = browser::window()?.location();
+    Ok(format!("{}{}{}", location.pathname()?, location.search()?, location.hash()?))
+}
+
+/// The location a [`ProtectedRoute`] started the login from, `None` for any other login.
+pub(crate) fn return_location(result: &RedirectLoginResult) -> Option<String> {
+    result.app_state().map(|ReturnTo { return_to }| return_to)
+}
+
+/// Replaces the callback URL with the location a [`ProtectedRoute`] started the login from.
+pub(crate) fn return_to(result: &RedirectLoginResult) -> Result<(), Auth0Error> {
+    if let Some(return_to) = return_location(result) {
+        browser::window()?.history()?.replace_state_with_url(&JsValue::NULL, "", Some(&return_to))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use wasm_bindgen_futures::spawn_local;
+use yew::{
+    function_component, html, use_effect_with_deps, use_state, Callback, Children, ContextProvider, Html, Properties,
+};
+
+use super::{
+    hooks::{use_shared_session, SharedSession},
+    protected,
+};
+use crate::{load_auth0_spa_js, Auth0Error, Auth0Service, ConfigOptions, RedirectLoginResult, AUTH0_SPA_JS_CDN};
+
+#[derive(Properties, PartialEq)]
+pub struct Auth0ProviderProps {
+    pub config: ConfigOptions,
+    /// Where to load `auth0-spa-js` from.
+    #[prop_or(AUTH0_SPA_JS_CDN.to_string())]
+    pub src: String,
+    /// Subresource integrity hash of `src`.
+    #[prop_or_default]
+    pub integrity: Option<String>,
+    /// Rendered until the client has been created and the redirect callback handled or the session restored.
+    #[prop_or_default]
+    pub loading: Html,
+    /// Called after a login redirected back to this page, with the `appState` passed to the login.
+    #[prop_or_default]
+    pub on_redirect_callback: Callback<RedirectLoginResult>,
+    /// Called if `auth0-spa-js` fails to load, the redirect callback fails or the session can't be restored.
+    #[prop_or_default]
+    pub on_error: Callback<Auth0Error>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Owns the [`Auth0Service`] of an app: loads `auth0-spa-js`, handles the redirect callback or restores the session
+/// with `checkSession`, and checks the session once for all the [`crate::hooks`] below it.
+///
+/// ```ignore
+/// html! {
+///     <Auth0Provider config={config}>
+///         <App />
+///     </Auth0Provider>
+/// }
+/// ```
+#[function_component(Auth0Provider)]
+pub fn auth0_provider(props: &Auth0ProviderProps) -> Html {
+    let service = use_state(|| None);
+
+    {
+        let (service, on_error) = (service.clone(), props.on_error.clone());
+        let on_redirect_callback = props.on_redirect_callback.clone();
+        use_effect_with_deps(
+            move |(config, src, integrity)| {
+                let (config, src, integrity) = (config.clone(), src.clone(), integrity.clone());
+                spawn_local(async move {
+                    let auth0 = match load_auth0_spa_js(&src, integrity.as_deref()).await {
+                        Ok(auth0) => auth0,
+                        Err(err) => return on_error.emit(err),
+                    };
+                    let client = Auth0Service::new(auth0, config);
+                    let callback = start(&client, &on_redirect_callback, &on_error).await;
+                    service.set(Some((client, callback)));
+                });
+                || ()
+            },
+            (props.config.clone(), props.src.clone(), props.integrity.clone()),
+        );
+    }
+
+    match &*service {
+        Some((service, callback)) => html! {
+            <Session service={service.clone()} callback={callback.clone()}>
+                { for props.children.iter() }
+            </Session>
+        },
+        None => props.loading.clone(),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Auth0ServiceProviderProps {
+    pub service: Auth0Service,
+    /// Rendered until the redirect callback has been handled or the session restored.
+    #[prop_or_default]
+    pub loading: Html,
+    /// Called after a login redirected back to this page, with the `appState` passed to the login.
+    #[prop_or_default]
+    pub on_redirect_callback: Callback<RedirectLoginResult>,
+    /// Called if the redirect callback fails or the session can't be restored.
+    #[prop_or_default]
+    pub on_error: Callback<Auth0Error>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Like [`Auth0Provider`], for a service the app has built itself, e.g. an [`Auth0Service::pkce`] one. Nothing is
+/// loaded.
+///
+/// ```ignore
+/// let service = use_state(|| Auth0Service::pkce(config));
+/// html! {
+///     <Auth0ServiceProvider service={(*service).clone()}>
+///         <App />
+///     </Auth0ServiceProvider>
+/// }
+/// ```
+#[function_component(Auth0ServiceProvider)]
+pub fn auth0_service_provider(props: &Auth0ServiceProviderProps) -> Html {
+    let started = use_state(|| None);
+
+    {
+        let (started, on_error) = (started.clone(), props.on_error.clone());
+        let on_redirect_callback = props.on_redirect_callback.clone();
+        use_effect_with_deps(
+            move |service| {
+                let service = service.clone();
+                started.set(None);
+                spawn_local(async move {
+                    let callback = start(&service, &on_redirect_callback, &on_error).await;
+                    started.set(Some(callback));
+                });
+                || ()
+            },
+            props.service.clone(),
+        );
+    }
+
+    match &*started {
+        Some(callback) => html! {
+            <Session service={props.service.clone()} callback={callback.clone()}>
+                { for props.children.iter() }
+            </Session>
+        },
+        None => props.loading.clone(),
+    }
+}
+
+/// Handles the redirect callback or, on any other page, restores the session Auth0 still has for the user, before the
+/// hooks check it for the first time. Returns the outcome of the callback, if there was one.
+async fn start(
+    service: &Auth0Service,
+    on_redirect_callback: &Callback<RedirectLoginResult>,
+    on_error: &Callback<Auth0Error>,
+) -> Option<Result<RedirectLoginResult, Auth0Error>> {
+    match service.process_redirect_callback().await {
+        Some(result) => {
+            let result = result.and_then(|result| protected::return_to(&result).map(|()| result));
+            match &result {
+                Ok(result) => on_redirect_callback.emit(result.clone()),
+                Err(err) => on_error.emit(err.clone()),
+            }
+            Some(result)
+        }
+        None => {
+            if let Err(err) = service.check_session().await {
+                on_error.emit(err);
+            }
+            None
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct SessionProps {
+    service: Auth0Service,
+    callback: Option<Result<RedirectLoginResult, Auth0Error>>,
+    children: Children,
+}
+
+/// Checks the session and shares it with the access token, a separate component because the hooks need the service
+/// before they run.
+#[function_component(Session)]
+fn session(props: &SessionProps) -> Html {
+    let shared = use_shared_session(&props.service, props.callback.clone());
+
+    html! {
+        <ContextProvider<Auth0Service> context={props.service.clone()}>
+            <ContextProvider<SharedSession> context={shared}>
+                { for props.children.iter() }
+            </ContextProvider<SharedSession>>
+        </ContextProvider<Auth0Service>>
+    }
+}
@@ -0,0 +1,110 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Function, Promise};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::Window;
+#[cfg(any(feature = "pkce", feature = "jwks"))]
+use {
+    crate::client::from_js,
+    serde::de::DeserializeOwned,
+    wasm_bindgen::JsCast,
+    web_sys::{RequestInit, Response},
+};
+#[cfg(feature = "pkce")]
+use {serde::Serialize, web_sys::Headers};
+
+use crate::Auth0Error;
+
+pub(crate) fn window() -> Result<Window, Auth0Error> {
+    web_sys::window().ok_or_else(|| Auth0Error::Js("window is not available".to_string()))
+}
+
+/// Milliseconds since the Unix epoch.
+pub(crate) fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+pub(crate) async fn sleep(millis: i32) -> Result<(), Auth0Error> {
+    let window = window()?;
+    let mut result = Ok(0);
+    let promise = Promise::new(&mut |resolve, _reject| {
+        result = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
+    });
+    result?;
+    JsFuture::from(promise).await?;
+    Ok(())
+}
+
+/// Wakes the [`sleep_until_woken`] it is passed to.
+#[derive(Clone, Default)]
+pub(crate) struct Wake(Rc<RefCell<Option<Function>>>);
+
+impl Wake {
+    pub fn wake(&self) {
+        if let Some(resolve) = self.0.borrow_mut().take() {
+            let _ = resolve.call0(&JsValue::UNDEFINED);
+        }
+    }
+}
+
+/// Like [`sleep`], but returns early when `wake` is woken.
+pub(crate) async fn sleep_until_woken(millis: i32, wake: &Wake) -> Result<(), Auth0Error> {
+    let window = window()?;
+    let mut result = Ok(0);
+    let promise = Promise::new(&mut |resolve, _reject| {
+        result = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
+        *wake.0.borrow_mut() = Some(resolve);
+    });
+    let handle = result?;
+    let slept = JsFuture::from(promise).await;
+    window.clear_timeout_with_handle(handle);
+    wake.0.borrow_mut().take();
+    slept?;
+    Ok(())
+}
+
+/// Random bytes from `crypto.getRandomValues`, base64url encoded.
+#[cfg(feature = "pkce")]
+pub(crate) fn random_string(len: usize) -> Result<String, Auth0Error> {
+    let mut bytes = vec![0; len];
+    window()?.crypto()?.get_random_values_with_u8_array(&mut bytes)?;
+    Ok(base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD))
+}
+
+#[cfg(feature = "jwks")]
+pub(crate) async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, Auth0Error> {
+    let mut init = RequestInit::new();
+    init.method("GET");
+
+    fetch_json(url, &init).await
+}
+
+/// `POST`s `body` as JSON and deserializes the JSON response.
+#[cfg(feature = "pkce")]
+pub(crate) async fn post_json<B: Serialize, T: DeserializeOwned>(url: &str, body: &B) -> Result<T, Auth0Error> {
+    let body = serde_json::to_string(body).map_err(|err| Auth0Error::Deserialize(err.to_string()))?;
+    let headers = Headers::new()?;
+    headers.set("Content-Type", "application/json")?;
+
+    let mut init = RequestInit::new();
+    init.method("POST")
+        .headers(&headers)
+        .body(Some(&JsValue::from_str(&body)));
+
+    fetch_json(url, &init).await
+}
+
+/// Error responses from Auth0 carry `error` / `error_description` and are mapped to [`Auth0Error`].
+#[cfg(any(feature = "pkce", feature = "jwks"))]
+async fn fetch_json<T: DeserializeOwned>(url: &str, init: &RequestInit) -> Result<T, Auth0Error> {
+    let response: Response = JsFuture::from(window()?.fetch_with_str_and_init(url, init))
+        .await?
+        .unchecked_into();
+    let json = JsFuture::from(response.json()?).await?;
+
+    if !response.ok() {
+        return Err(json.into());
+    }
+    from_js(&json)
+}
@@ -0,0 +1,115 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Array, Promise};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+use crate::{browser, Auth0Error};
+
+use super::{CacheFuture, TokenCache};
+
+const DATABASE_VERSION: u32 = 1;
+const STORE_NAME: &str = "tokens";
+
+/// Stores entries in an IndexedDB object store. The database is opened on first use.
+#[derive(Clone)]
+pub struct IndexedDbCache {
+    name: Rc<str>,
+    database: Rc<RefCell<Option<IdbDatabase>>>,
+}
+
+impl IndexedDbCache {
+    pub fn new(database_name: &str) -> Self {
+        IndexedDbCache {
+            name: database_name.into(),
+            database: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    async fn store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore, Auth0Error> {
+        let database = self.database.borrow().clone();
+        let database = match database {
+            Some(database) => database,
+            None => {
+                let database = open(&self.name).await?;
+                *self.database.borrow_mut() = Some(database.clone());
+                database
+            }
+        };
+
+        Ok(database.transaction_with_str_and_mode(STORE_NAME, mode)?.object_store(STORE_NAME)?)
+    }
+}
+
+impl TokenCache for IndexedDbCache {
+    fn get(&self, key: &str) -> CacheFuture<Option<String>> {
+        let (cache, key) = (self.clone(), JsValue::from_str(key));
+        Box::pin(async move {
+            let request = cache.store(IdbTransactionMode::Readonly).await?.get(&key)?;
+            Ok(complete(&request).await?.as_string())
+        })
+    }
+
+    fn set(&self, key: &str, value: String) -> CacheFuture<()> {
+        let (cache, key) = (self.clone(), JsValue::from_str(key));
+        Box::pin(async move {
+            let request = cache
+                .store(IdbTransactionMode::Readwrite)
+                .await?
+                .put_with_key(&JsValue::from_str(&value), &key)?;
+            complete(&request).await?;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, key: &str) -> CacheFuture<()> {
+        let (cache, key) = (self.clone(), JsValue::from_str(key));
+        Box::pin(async move {
+            let request = cache.store(IdbTransactionMode::Readwrite).await?.delete(&key)?;
+            complete(&request).await?;
+            Ok(())
+        })
+    }
+
+    fn all_keys(&self) -> CacheFuture<Vec<String>> {
+        let cache = self.clone();
+        Box::pin(async move {
+            let request = cache.store(IdbTransactionMode::Readonly).await?.get_all_keys()?;
+            let keys: Array = complete(&request).await?.unchecked_into();
+            Ok(keys.iter().filter_map(|key| key.as_string()).collect())
+        })
+    }
+}
+
+async fn open(name: &str) -> Result<IdbDatabase, Auth0Error> {
+    let factory = browser::window()?
+        .indexed_db()?
+        .ok_or_else(|| Auth0Error::Js("IndexedDB is not available".to_string()))?;
+    let request: IdbOpenDbRequest = factory.open_with_u32(name, DATABASE_VERSION)?;
+
+    let on_upgrade_needed = {
+        let request = request.clone();
+        Closure::once(move || {
+            if let Ok(database) = request.result() {
+                let _ = database.unchecked_into::<IdbDatabase>().create_object_store(STORE_NAME);
+            }
+        })
+    };
+    request.set_onupgradeneeded(Some(on_upgrade_needed.as_ref().unchecked_ref()));
+    let database = complete(&request).await;
+    request.set_onupgradeneeded(None);
+
+    Ok(database?.unchecked_into())
+}
+
+/// Resolves with the request's result once it has succeeded.
+async fn complete(request: &IdbRequest) -> Result<JsValue, Auth0Error> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise).await?;
+
+    Ok(request.result()?)
+}
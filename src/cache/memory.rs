@@ -0,0 +1,51 @@
+use std::{cell::RefCell, collections::HashMap, future, rc::Rc, time::Duration};
+
+use crate::browser;
+
+use super::{CacheFuture, TokenCache};
+
+/// Keeps entries in memory for at most `ttl`, whatever the lifetime of the tokens in them. Nothing survives a reload.
+#[derive(Clone)]
+pub struct InMemoryCache {
+    ttl: Duration,
+    entries: Rc<RefCell<HashMap<String, (String, f64)>>>,
+}
+
+impl InMemoryCache {
+    pub fn new(ttl: Duration) -> Self {
+        InMemoryCache {
+            ttl,
+            entries: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    fn evict_expired(&self) {
+        let now = browser::now();
+        self.entries.borrow_mut().retain(|_, (_, expires_at)| *expires_at > now);
+    }
+}
+
+impl TokenCache for InMemoryCache {
+    fn get(&self, key: &str) -> CacheFuture<Option<String>> {
+        self.evict_expired();
+        let value = self.entries.borrow().get(key).map(|(value, _)| value.clone());
+        Box::pin(future::ready(Ok(value)))
+    }
+
+    fn set(&self, key: &str, value: String) -> CacheFuture<()> {
+        let expires_at = browser::now() + self.ttl.as_millis() as f64;
+        self.entries.borrow_mut().insert(key.to_string(), (value, expires_at));
+        Box::pin(future::ready(Ok(())))
+    }
+
+    fn remove(&self, key: &str) -> CacheFuture<()> {
+        self.entries.borrow_mut().remove(key);
+        Box::pin(future::ready(Ok(())))
+    }
+
+    fn all_keys(&self) -> CacheFuture<Vec<String>> {
+        self.evict_expired();
+        let keys = self.entries.borrow().keys().cloned().collect();
+        Box::pin(future::ready(Ok(keys)))
+    }
+}
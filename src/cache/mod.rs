@@ -0,0 +1,130 @@
+mod indexed_db;
+mod memory;
+mod session;
+
+use std::{fmt, future::Future, pin::Pin, rc::Rc};
+
+use js_sys::{Array, Function, Object, Promise, Reflect, JSON};
+use wasm_bindgen::{closure::WasmClosure, prelude::*, JsCast};
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::Auth0Error;
+
+pub use self::{indexed_db::IndexedDbCache, memory::InMemoryCache, session::SessionStorageCache};
+
+pub type CacheFuture<T> = Pin<Box<dyn Future<Output = Result<T, Auth0Error>>>>;
+
+/// Storage for the entries `auth0-spa-js` caches, set with [`crate::ConfigOptionsBuilder::cache`].
+/// Values are the JSON encoded entries, keys are chosen by `auth0-spa-js`.
+pub trait TokenCache {
+    fn get(&self, key: &str) -> CacheFuture<Option<String>>;
+    fn set(&self, key: &str, value: String) -> CacheFuture<()>;
+    fn remove(&self, key: &str) -> CacheFuture<()>;
+    fn all_keys(&self) -> CacheFuture<Vec<String>>;
+}
+
+/// A shared [`TokenCache`]. Handles are always equal, so that a [`crate::ConfigOptions`] rebuilt on every render with
+/// a new cache still equals the previous one and does not recreate the client. Whether a cache is set still counts.
+#[derive(Clone)]
+pub(crate) struct CacheHandle(pub Rc<dyn TokenCache>);
+
+impl PartialEq for CacheHandle {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for CacheHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CacheHandle")
+    }
+}
+
+impl CacheHandle {
+    /// The `ICache` object `auth0-spa-js` expects in its `cache` option.
+    pub fn to_js(&self) -> Object {
+        let object = Object::new();
+
+        let cache = self.0.clone();
+        set_function(
+            &object,
+            "get",
+            Closure::wrap(Box::new(move |key: String| {
+                let get = cache.get(&key);
+                future_to_promise(async move {
+                    match get.await? {
+                        Some(value) => JSON::parse(&value),
+                        None => Ok(JsValue::UNDEFINED),
+                    }
+                })
+            }) as Box<dyn FnMut(String) -> Promise>),
+        );
+
+        let cache = self.0.clone();
+        set_function(
+            &object,
+            "set",
+            Closure::wrap(Box::new(move |key: String, entry: JsValue| {
+                let set = JSON::stringify(&entry).map(|value| cache.set(&key, value.into()));
+                future_to_promise(async move {
+                    set?.await?;
+                    Ok(JsValue::UNDEFINED)
+                })
+            }) as Box<dyn FnMut(String, JsValue) -> Promise>),
+        );
+
+        let cache = self.0.clone();
+        set_function(
+            &object,
+            "remove",
+            Closure::wrap(Box::new(move |key: String| {
+                let remove = cache.remove(&key);
+                future_to_promise(async move {
+                    remove.await?;
+                    Ok(JsValue::UNDEFINED)
+                })
+            }) as Box<dyn FnMut(String) -> Promise>),
+        );
+
+        let cache = self.0.clone();
+        set_function(
+            &object,
+            "allKeys",
+            Closure::wrap(Box::new(move || {
+                let all_keys = cache.all_keys();
+                future_to_promise(async move {
+                    let keys = all_keys.await?;
+                    Ok(keys.into_iter().map(JsValue::from).collect::<Array>().into())
+                })
+            }) as Box<dyn FnMut() -> Promise>),
+        );
+
+        object
+    }
+}
+
+/// The closures are handed over to the JS garbage collector, they live as long as the client holding the object.
+fn set_function<T: ?Sized + WasmClosure>(object: &Object, name: &str, closure: Closure<T>) {
+    let function: Function = closure.into_js_value().unchecked_into();
+    let _ = Reflect::set(object, &JsValue::from_str(name), &function);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::InMemoryCache;
+    use crate::ConfigOptions;
+
+    fn config() -> ConfigOptions {
+        ConfigOptions::builder("tenant.eu.auth0.com", "client")
+            .cache(InMemoryCache::new(Duration::from_secs(60)))
+            .build()
+    }
+
+    #[test]
+    fn rebuilt_config_with_a_new_cache_is_equal() {
+        assert_eq!(config(), config());
+        assert_ne!(config(), ConfigOptions::builder("tenant.eu.auth0.com", "client").build());
+    }
+}
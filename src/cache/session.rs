@@ -0,0 +1,53 @@
+use std::future;
+
+use web_sys::Storage;
+
+use crate::{browser, Auth0Error};
+
+use super::{CacheFuture, TokenCache};
+
+const KEY_PREFIX: &str = "@@auth0spajs@@";
+
+/// Stores entries in `sessionStorage`: they survive reloads but are scoped to the tab and cleared when it closes.
+#[derive(Clone, Default)]
+pub struct SessionStorageCache;
+
+impl SessionStorageCache {
+    pub fn new() -> Self {
+        SessionStorageCache
+    }
+}
+
+impl TokenCache for SessionStorageCache {
+    fn get(&self, key: &str) -> CacheFuture<Option<String>> {
+        let value = storage().and_then(|storage| Ok(storage.get_item(key)?));
+        Box::pin(future::ready(value))
+    }
+
+    fn set(&self, key: &str, value: String) -> CacheFuture<()> {
+        let result = storage().and_then(|storage| Ok(storage.set_item(key, &value)?));
+        Box::pin(future::ready(result))
+    }
+
+    fn remove(&self, key: &str) -> CacheFuture<()> {
+        let result = storage().and_then(|storage| Ok(storage.remove_item(key)?));
+        Box::pin(future::ready(result))
+    }
+
+    fn all_keys(&self) -> CacheFuture<Vec<String>> {
+        let keys = storage().and_then(|storage| {
+            let keys = (0..storage.length()?)
+                .filter_map(|index| storage.key(index).ok().flatten())
+                .filter(|key| key.starts_with(KEY_PREFIX))
+                .collect();
+            Ok(keys)
+        });
+        Box::pin(future::ready(keys))
+    }
+}
+
+fn storage() -> Result<Storage, Auth0Error> {
+    browser::window()?
+        .session_storage()?
+        .ok_or_else(|| Auth0Error::Js("sessionStorage is not available".to_string()))
+}
@@ -0,0 +1,94 @@
+use js_sys::{Object, Reflect};
+use serde::de::DeserializeOwned;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::{
+    Auth0Client, Auth0Error, GetIdTokenClaimsOptions, GetTokenSilentlyOptions,
+    GetTokenWithPopupOptions, GetUserOptions, IdToken, LogoutOptions, LogoutUrlOptions, PopupConfigOptions,
+    PopupLoginOptions, RedirectLoginOptions, RedirectLoginResult, TokenResponse, User,
+};
+
+impl Auth0Client {
+    pub async fn build_authorize_url(&self, options: Option<RedirectLoginOptions>) -> Result<String, Auth0Error> {
+        from_js(&self.js_build_authorize_url(options).await?)
+    }
+
+    pub fn build_logout_url(&self, options: Option<LogoutUrlOptions>) -> Result<String, Auth0Error> {
+        from_js(&self.js_build_logout_url(options)?)
+    }
+
+    pub async fn check_session(&self, options: Option<GetTokenSilentlyOptions>) -> Result<(), Auth0Error> {
+        self.js_check_session(options).await?;
+        Ok(())
+    }
+
+    pub async fn get_id_token_claims<T: DeserializeOwned>(
+        &self,
+        options: Option<GetIdTokenClaimsOptions>,
+    ) -> Result<Option<IdToken<T>>, Auth0Error> {
+        from_js(&self.js_get_id_token_claims(options).await?)
+    }
+
+    pub async fn get_token_silently(&self, options: Option<GetTokenSilentlyOptions>) -> Result<String, Auth0Error> {
+        from_js(&self.js_get_token_silently(options).await?)
+    }
+
+    /// `getTokenSilently` with `detailedResponse` set, returning the id token, expiry and granted scopes as well.
+    pub async fn get_token_silently_detailed(
+        &self,
+        options: Option<GetTokenSilentlyOptions>,
+    ) -> Result<TokenResponse, Auth0Error> {
+        let options = options.unwrap_or_else(|| Object::new().unchecked_into());
+        Reflect::set(&options, &JsValue::from_str("detailedResponse"), &JsValue::TRUE)?;
+
+        from_js(&self.js_get_token_silently(Some(options)).await?)
+    }
+
+    pub async fn get_token_with_popup(
+        &self,
+        options: Option<GetTokenWithPopupOptions>,
+        config: Option<PopupConfigOptions>,
+    ) -> Result<String, Auth0Error> {
+        from_js(&self.js_get_token_with_popup(options, config).await?)
+    }
+
+    pub async fn get_user<Extra: DeserializeOwned>(
+        &self,
+        options: Option<GetUserOptions>,
+    ) -> Result<Option<User<Extra>>, Auth0Error> {
+        from_js(&self.js_get_user(options).await?)
+    }
+
+    pub async fn handle_redirect_callback(&self, url: Option<String>) -> Result<RedirectLoginResult, Auth0Error> {
+        from_js(&self.js_handle_redirect_callback(url).await?)
+    }
+
+    pub async fn is_authenticated(&self) -> Result<bool, Auth0Error> {
+        from_js(&self.js_is_authenticated().await?)
+    }
+
+    pub async fn login_with_popup(
+        &self,
+        options: Option<PopupLoginOptions>,
+        config: Option<PopupConfigOptions>,
+    ) -> Result<(), Auth0Error> {
+        self.js_login_with_popup(options, config).await?;
+        Ok(())
+    }
+
+    pub async fn login_with_redirect(&self, options: Option<RedirectLoginOptions>) -> Result<(), Auth0Error> {
+        self.js_login_with_redirect(options).await?;
+        Ok(())
+    }
+
+    pub fn logout(&self, options: Option<LogoutOptions>) -> Result<(), Auth0Error> {
+        self.js_logout(options)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn from_js<T: DeserializeOwned>(value: &JsValue) -> Result<T, Auth0Error> {
+    value
+        .into_serde()
+        .map_err(|err| Auth0Error::Deserialize(err.to_string()))
+}
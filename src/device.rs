@@ -0,0 +1,312 @@
+//! OAuth 2.0 Device Authorization Grant for native applications, e.g. CLIs logging in to the same tenant as the SPA.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{Auth0Error, ConfigOptions, TokenOptions, TokenResponse};
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEFAULT_INTERVAL: u64 = 5;
+
+/// The code the user has to confirm at `verification_uri`.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// `verification_uri` with the `user_code` already filled in.
+    pub verification_uri_complete: Option<String>,
+    /// Seconds until `device_code` expires.
+    pub expires_in: u64,
+    /// Seconds to wait between polls of the token endpoint.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+/// Tokens returned once the user has confirmed the code.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DeviceToken {
+    pub response: TokenResponse,
+    /// Only issued when `offline_access` was requested.
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    access_token: String,
+    id_token: String,
+    expires_in: u64,
+    scope: Option<String>,
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+/// Blocking Device Authorization client for the tenant in `ConfigOptions`.
+pub struct DeviceClient {
+    config: ConfigOptions,
+    base_url: String,
+    agent: ureq::Agent,
+    /// Unit of `expires_in` and `interval`, shortened by the tests.
+    tick: Duration,
+}
+
+impl DeviceClient {
+    pub fn new(config: ConfigOptions) -> Self {
+        DeviceClient {
+            base_url: format!("https://{}", config.domain),
+            config,
+            agent: ureq::AgentBuilder::new().build(),
+            tick: Duration::from_secs(1),
+        }
+    }
+
+    /// Sends requests to `base_url` instead of `https://{domain}`, e.g. to a local mock of the tenant.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn config(&self) -> &ConfigOptions {
+        &self.config
+    }
+
+    /// Starts a login by requesting a device and user code from `/oauth/device/code`.
+    pub fn request_code(&self, options: Option<TokenOptions>) -> Result<DeviceCode, Auth0Error> {
+        let options = options.unwrap_or_default();
+        let scope = self.config.merged_scope(options.scope.as_deref());
+
+        let mut form = vec![("client_id", self.config.client_id.as_str()), ("scope", scope.as_str())];
+        if let Some(audience) = options.audience.as_deref().or(self.config.audience.as_deref()) {
+            form.push(("audience", audience));
+        }
+
+        self.post("/oauth/device/code", &form)
+    }
+
+    /// Polls `/oauth/token` until the user has confirmed or denied `code`, or it has expired.
+    /// Blocks the current thread in the meantime.
+    pub fn poll_token(&self, code: &DeviceCode) -> Result<DeviceToken, Auth0Error> {
+        let deadline = Instant::now() + self.tick * code.expires_in as u32;
+        let mut interval = code.interval;
+
+        let form = [
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("device_code", code.device_code.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+        ];
+
+        loop {
+            match self.post::<TokenEndpointResponse>("/oauth/token", &form) {
+                Ok(token) => {
+                    return Ok(DeviceToken {
+                        response: TokenResponse {
+                            access_token: token.access_token,
+                            id_token: token.id_token,
+                            expires_in: token.expires_in,
+                            scope: token.scope,
+                        },
+                        refresh_token: token.refresh_token,
+                    })
+                }
+                Err(Auth0Error::Other { error, .. }) if error == "authorization_pending" => {}
+                Err(Auth0Error::Other { error, .. }) if error == "slow_down" => interval += DEFAULT_INTERVAL,
+                Err(err) => return Err(err),
+            }
+
+            let wait = self.tick * interval as u32;
+            if Instant::now() + wait > deadline {
+                return Err(Auth0Error::Timeout("The device code expired before it was confirmed".to_string()));
+            }
+            thread::sleep(wait);
+        }
+    }
+
+    /// [`DeviceClient::request_code`] followed by [`DeviceClient::poll_token`]. `prompt` is called with the code
+    /// so it can be shown to the user.
+    pub fn login(
+        &self,
+        options: Option<TokenOptions>,
+        prompt: impl FnOnce(&DeviceCode),
+    ) -> Result<DeviceToken, Auth0Error> {
+        let code = self.request_code(options)?;
+        prompt(&code);
+        self.poll_token(&code)
+    }
+
+    fn post<T: DeserializeOwned>(&self, path: &str, form: &[(&str, &str)]) -> Result<T, Auth0Error> {
+        let url = format!("{}{}", self.base_url, path);
+
+        match self.agent.post(&url).send_form(form) {
+            Ok(response) => response
+                .into_json()
+                .map_err(|err| Auth0Error::Deserialize(err.to_string())),
+            Err(ureq::Error::Status(status, response)) => match response.into_json::<ErrorResponse>() {
+                Ok(body) => Err(Auth0Error::from_code(&body.error, body.error_description)),
+                Err(_) => Err(Auth0Error::Request(format!("{} responded with status {}", url, status))),
+            },
+            Err(err) => Err(Auth0Error::Request(err.to_string())),
+        }
+    }
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+
+    const TOKEN: &str = r#"{"access_token":"at","id_token":"it","expires_in":86400,"scope":"openid","refresh_token":"rt"}"#;
+    const PENDING: (u16, &str) = (403, r#"{"error":"authorization_pending"}"#);
+    const SLOW_DOWN: (u16, &str) = (429, r#"{"error":"slow_down"}"#);
+
+    /// Answers one request per response in `responses`, in order. Resolves to the `path body` of every request.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut form = vec![0; content_length];
+                reader.read_exact(&mut form).unwrap();
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                requests.push(format!("{} {}", path, String::from_utf8(form).unwrap()));
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+            requests
+        });
+
+        (base_url, server)
+    }
+
+    fn client(base_url: &str) -> DeviceClient {
+        let config = ConfigOptions::builder("example.auth0.com", "client").audience("https://api").build();
+        DeviceClient {
+            tick: Duration::from_millis(1),
+            ..DeviceClient::new(config).with_base_url(base_url)
+        }
+    }
+
+    fn code(expires_in: u64) -> DeviceCode {
+        DeviceCode {
+            device_code: "device".to_string(),
+            user_code: "ABCD-EFGH".to_string(),
+            verification_uri: "https://example.auth0.com/activate".to_string(),
+            verification_uri_complete: None,
+            expires_in,
+            interval: 1,
+        }
+    }
+
+    #[test]
+    fn requests_a_code() {
+        let (base_url, server) = serve(vec![(
+            200,
+            r#"{"device_code":"device","user_code":"ABCD-EFGH","verification_uri":"https://example.auth0.com/activate","expires_in":900}"#,
+        )]);
+
+        let code = client(&base_url).request_code(None).unwrap();
+
+        assert_eq!(code.user_code, "ABCD-EFGH");
+        assert_eq!(code.interval, DEFAULT_INTERVAL);
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("/oauth/device/code client_id=client&scope=openid"));
+        assert!(requests[0].ends_with("&audience=https%3A%2F%2Fapi"));
+    }
+
+    #[test]
+    fn polls_through_pending_and_slow_down() {
+        let (base_url, server) = serve(vec![PENDING, SLOW_DOWN, PENDING, (200, TOKEN)]);
+
+        let started = Instant::now();
+        let token = client(&base_url).poll_token(&code(900)).unwrap();
+
+        assert_eq!(token.response.access_token, "at");
+        assert_eq!(token.refresh_token.as_deref(), Some("rt"));
+        // One tick after the first pending, six after slow_down raised the interval and six after the second pending.
+        assert!(started.elapsed() >= Duration::from_millis(13));
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests
+            .iter()
+            .all(|request| request.starts_with("/oauth/token grant_type=urn") && request.contains("device_code=device")));
+    }
+
+    #[test]
+    fn fails_when_the_code_expired() {
+        let expired = (400, r#"{"error":"expired_token","error_description":"expired"}"#);
+        let (base_url, server) = serve(vec![PENDING, expired]);
+
+        let err = client(&base_url).poll_token(&code(900)).unwrap_err();
+
+        assert_eq!(err.error(), "expired_token");
+        assert_eq!(err.error_description(), "expired");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn fails_when_the_user_denied_access() {
+        let (base_url, server) = serve(vec![(403, r#"{"error":"access_denied","error_description":"denied"}"#)]);
+
+        let err = client(&base_url).poll_token(&code(900)).unwrap_err();
+
+        assert_eq!(err.error(), "access_denied");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn times_out_at_the_deadline() {
+        let (base_url, server) = serve(vec![PENDING]);
+
+        // The next poll would be after the code has expired.
+        let err = client(&base_url).poll_token(&code(0)).unwrap_err();
+
+        assert!(matches!(err, Auth0Error::Timeout(_)));
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Message of the `Error` `auth0-spa-js` throws when there is no transaction for a redirect callback.
+const INVALID_STATE: &str = "Invalid state";
+/// Start of the message of the `Error` `auth0-spa-js` throws when `window.open` returned `null`.
+const POPUP_BLOCKED: &str = "Unable to open a popup";
+
+/// Error returned by the `Auth0Client` calls, mapped from the `error` / `error_description`
+/// properties of the rejected JS error.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Auth0Error {
+    LoginRequired(String),
+    ConsentRequired(String),
+    InteractionRequired(String),
+    MfaRequired(String),
+    InvalidGrant(String),
+    MissingRefreshToken(String),
+    Timeout(String),
+    PopupClosed(String),
+    PopupBlocked(String),
+    InvalidState(String),
+    /// A JWT that could not be decoded.
+    InvalidToken(String),
+    /// Any other `error` code returned by Auth0.
+    Other { error: String, description: String },
+    /// The `auth0-spa-js` script could not be loaded.
+    LoadFailed(String),
+    /// A request to Auth0 that failed without an OAuth error response, e.g. a network error.
+    Request(String),
+    /// A plain JS exception without an `error` code.
+    Js(String),
+    /// The value returned by `auth0-spa-js` could not be converted to the expected Rust type.
+    Deserialize(String),
+}
+
+impl Auth0Error {
+    /// The OAuth `error` code, e.g. `login_required`.
+    pub fn error(&self) -> &str {
+        match self {
+            Auth0Error::LoginRequired(_) => "login_required",
+            Auth0Error::ConsentRequired(_) => "consent_required",
+            Auth0Error::InteractionRequired(_) => "interaction_required",
+            Auth0Error::MfaRequired(_) => "mfa_required",
+            Auth0Error::InvalidGrant(_) => "invalid_grant",
+            Auth0Error::MissingRefreshToken(_) => "missing_refresh_token",
+            Auth0Error::Timeout(_) => "timeout",
+            Auth0Error::PopupClosed(_) => "cancelled",
+            Auth0Error::PopupBlocked(_) => "popup_blocked",
+            Auth0Error::InvalidState(_) => "invalid_state",
+            Auth0Error::InvalidToken(_) => "invalid_token",
+            Auth0Error::Other { error, .. } => error,
+            Auth0Error::LoadFailed(_) => "load_failed",
+            Auth0Error::Request(_) => "request_error",
+            Auth0Error::Js(_) => "js_error",
+            Auth0Error::Deserialize(_) => "deserialize_error",
+        }
+    }
+
+    pub fn error_description(&self) -> &str {
+        match self {
+            Auth0Error::LoginRequired(description)
+            | Auth0Error::ConsentRequired(description)
+            | Auth0Error::InteractionRequired(description)
+            | Auth0Error::MfaRequired(description)
+            | Auth0Error::InvalidGrant(description)
+            | Auth0Error::MissingRefreshToken(description)
+            | Auth0Error::Timeout(description)
+            | Auth0Error::PopupClosed(description)
+            | Auth0Error::PopupBlocked(description)
+            | Auth0Error::InvalidState(description)
+            | Auth0Error::InvalidToken(description)
+            | Auth0Error::Other { description, .. }
+            | Auth0Error::LoadFailed(description)
+            | Auth0Error::Request(description)
+            | Auth0Error::Js(description)
+            | Auth0Error::Deserialize(description) => description,
+        }
+    }
+
+    /// Maps an OAuth `error` code and its `error_description` to the matching variant, the inverse of
+    /// [`Auth0Error::error`] and [`Auth0Error::error_description`].
+    pub fn from_code(error: &str, description: impl Into<String>) -> Self {
+        let description = description.into();
+        match error {
+            "login_required" => Auth0Error::LoginRequired(description),
+            "consent_required" => Auth0Error::ConsentRequired(description),
+            "interaction_required" => Auth0Error::InteractionRequired(description),
+            "mfa_required" => Auth0Error::MfaRequired(description),
+            "invalid_grant" => Auth0Error::InvalidGrant(description),
+            "missing_refresh_token" => Auth0Error::MissingRefreshToken(description),
+            "timeout" => Auth0Error::Timeout(description),
+            "cancelled" => Auth0Error::PopupClosed(description),
+            "popup_blocked" => Auth0Error::PopupBlocked(description),
+            "state_mismatch" | "invalid_state" => Auth0Error::InvalidState(description),
+            "invalid_token" => Auth0Error::InvalidToken(description),
+            "load_failed" => Auth0Error::LoadFailed(description),
+            "request_error" => Auth0Error::Request(description),
+            "js_error" => Auth0Error::Js(description),
+            "deserialize_error" => Auth0Error::Deserialize(description),
+            error => Auth0Error::Other {
+                error: error.to_string(),
+                description,
+            },
+        }
+    }
+
+    /// Whether the user has to go through an interactive login to recover from this error.
+    pub fn requires_login(&self) -> bool {
+        matches!(
+            self,
+            Auth0Error::LoginRequired(_)
+                | Auth0Error::ConsentRequired(_)
+                | Auth0Error::InteractionRequired(_)
+                | Auth0Error::MissingRefreshToken(_)
+                | Auth0Error::InvalidGrant(_)
+        )
+    }
+
+    /// `auth0-spa-js` throws a few errors as plain `Error`s without an `error` code, those are told apart by their
+    /// message. Anything else is a [`Auth0Error::Js`].
+    fn from_message(description: String) -> Self {
+        if description == INVALID_STATE {
+            Auth0Error::InvalidState(description)
+        } else if description.starts_with(POPUP_BLOCKED) {
+            Auth0Error::PopupBlocked(description)
+        } else {
+            Auth0Error::Js(description)
+        }
+    }
+}
+
+impl fmt::Display for Auth0Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.error(), self.error_description())
+    }
+}
+
+impl std::error::Error for Auth0Error {}
+
+impl From<JsValue> for Auth0Error {
+    fn from(value: JsValue) -> Self {
+        let description = string_property(&value, "error_description")
+            .or_else(|| value.dyn_ref::<js_sys::Error>().map(|err| String::from(err.message())))
+            .or_else(|| value.as_string())
+            .unwrap_or_default();
+
+        match string_property(&value, "error") {
+            Some(error) => Auth0Error::from_code(&error, description),
+            None => Auth0Error::from_message(description),
+        }
+    }
+}
+
+impl From<Auth0Error> for JsValue {
+    fn from(err: Auth0Error) -> Self {
+        js_sys::Error::new(&err.to_string()).into()
+    }
+}
+
+fn string_property(value: &JsValue, key: &str) -> Option<String> {
+    if !value.is_object() {
+        return None;
+    }
+    js_sys::Reflect::get(value, &JsValue::from_str(key))
+        .ok()
+        .and_then(|property| property.as_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_variant() -> Vec<Auth0Error> {
+        let description = || "description".to_string();
+        vec![
+            Auth0Error::LoginRequired(description()),
+            Auth0Error::ConsentRequired(description()),
+            Auth0Error::InteractionRequired(description()),
+            Auth0Error::MfaRequired(description()),
+            Auth0Error::InvalidGrant(description()),
+            Auth0Error::MissingRefreshToken(description()),
+            Auth0Error::Timeout(description()),
+            Auth0Error::PopupClosed(description()),
+            Auth0Error::PopupBlocked(description()),
+            Auth0Error::InvalidState(description()),
+            Auth0Error::InvalidToken(description()),
+            Auth0Error::Other {
+                error: "access_denied".to_string(),
+                description: description(),
+            },
+            Auth0Error::LoadFailed(description()),
+            Auth0Error::Request(description()),
+            Auth0Error::Js(description()),
+            Auth0Error::Deserialize(description()),
+        ]
+    }
+
+    #[test]
+    fn from_code_is_the_inverse_of_error() {
+        for err in every_variant() {
+            assert_eq!(Auth0Error::from_code(err.error(), err.error_description()), err);
+        }
+    }
+
+    #[test]
+    fn maps_the_codes_of_auth0_spa_js() {
+        let cases = [
+            ("login_required", Auth0Error::LoginRequired("d".to_string())),
+            ("popup_blocked", Auth0Error::PopupBlocked("d".to_string())),
+            ("cancelled", Auth0Error::PopupClosed("d".to_string())),
+            ("missing_refresh_token", Auth0Error::MissingRefreshToken("d".to_string())),
+            ("state_mismatch", Auth0Error::InvalidState("d".to_string())),
+            ("timeout", Auth0Error::Timeout("d".to_string())),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(Auth0Error::from_code(code, "d"), expected, "{}", code);
+        }
+    }
+
+    #[test]
+    fn unknown_codes_fall_through_to_other() {
+        let err = Auth0Error::from_code("unauthorized", "Access denied.");
+
+        assert_eq!(
+            err,
+            Auth0Error::Other {
+                error: "unauthorized".to_string(),
+                description: "Access denied.".to_string(),
+            }
+        );
+        assert_eq!(err.to_string(), "unauthorized: Access denied.");
+    }
+
+    #[test]
+    fn maps_the_messages_of_errors_without_code() {
+        let message = |message: &str| Auth0Error::from_message(message.to_string());
+
+        assert_eq!(message("Invalid state"), Auth0Error::InvalidState("Invalid state".to_string()));
+        assert!(matches!(
+            message("Unable to open a popup for loginWithPopup - window.open returned `null`"),
+            Auth0Error::PopupBlocked(_)
+        ));
+        assert_eq!(message("Invalid state."), Auth0Error::Js("Invalid state.".to_string()));
+        assert_eq!(message("Failed to fetch"), Auth0Error::Js("Failed to fetch".to_string()));
+    }
+
+    #[test]
+    fn requires_login() {
+        assert!(Auth0Error::from_code("login_required", "").requires_login());
+        assert!(Auth0Error::from_code("missing_refresh_token", "").requires_login());
+        assert!(!Auth0Error::from_code("timeout", "").requires_login());
+        assert!(!Auth0Error::from_code("popup_blocked", "").requires_login());
+    }
+}
@@ -0,0 +1,318 @@
+//! Signature and claim verification of ID and access tokens against the tenant's JSON Web Key Set.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{browser, jwt, model::one_or_many, Auth0Error, ConfigOptions, IdToken};
+
+const DEFAULT_LEEWAY: u32 = 60;
+/// Seconds after a fetch of the key set during which an unknown `kid` does not fetch it again.
+const REFETCH_COOLDOWN: f64 = 30.0;
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RegisteredClaims {
+    iss: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    aud: Vec<String>,
+    exp: Option<f64>,
+    nbf: Option<f64>,
+    nonce: Option<String>,
+    azp: Option<String>,
+}
+
+/// Verifies RS256 tokens issued by the configured tenant. The key set is fetched from
+/// `https://{domain}/.well-known/jwks.json` on first use and again only when a token is signed with an unknown `kid`,
+/// at most once every 30 seconds so that crafted tokens can't make it fetch over and over.
+pub struct JwksVerifier {
+    jwks_uri: String,
+    issuer: String,
+    client_id: String,
+    leeway: u32,
+    keys: RefCell<HashMap<String, RsaPublicKey>>,
+    /// When the key set was last fetched, in seconds since the Unix epoch.
+    fetched_at: Cell<Option<f64>>,
+}
+
+impl JwksVerifier {
+    pub fn new(config: &ConfigOptions) -> Self {
+        JwksVerifier {
+            jwks_uri: format!("https://{}/.well-known/jwks.json", config.domain),
+            issuer: config.issuer_url(),
+            client_id: config.client_id.clone(),
+            leeway: config.leeway.unwrap_or(DEFAULT_LEEWAY),
+            keys: RefCell::new(HashMap::new()),
+            fetched_at: Cell::new(None),
+        }
+    }
+
+    /// Verifies an ID token issued to this client. `nonce` is checked when given.
+    pub async fn verify_id_token<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        nonce: Option<&str>,
+    ) -> Result<IdToken<T>, Auth0Error> {
+        let mut claims = self.verify(token, &self.client_id, nonce).await?;
+        claims.insert("__raw".to_string(), Value::String(token.to_string()));
+
+        serde_json::from_value(Value::Object(claims)).map_err(|err| Auth0Error::Deserialize(err.to_string()))
+    }
+
+    /// Verifies an access token issued for `audience`.
+    pub async fn verify_access_token<C: DeserializeOwned>(&self, token: &str, audience: &str) -> Result<C, Auth0Error> {
+        let claims = self.verify(token, audience, None).await?;
+
+        serde_json::from_value(Value::Object(claims)).map_err(|err| Auth0Error::Deserialize(err.to_string()))
+    }
+
+    async fn verify(&self, token: &str, audience: &str, nonce: Option<&str>) -> Result<Map<String, Value>, Auth0Error> {
+        let header = jwt::decode_header(token)?;
+        if header.alg != "RS256" {
+            return Err(invalid(format!("Signature algorithm of \"{}\" is not supported", header.alg)));
+        }
+        let kid = header.kid.ok_or_else(|| invalid("Token has no key id (kid)"))?;
+        let key = self.key(&kid).await?;
+
+        let (signing_input, signature) = jwt::signed_parts(token)?;
+        key.verify(
+            Pkcs1v15Sign::new::<Sha256>(),
+            &Sha256::digest(signing_input.as_bytes()),
+            &signature,
+        )
+        .map_err(|_| invalid("Invalid token signature"))?;
+
+        let claims: Map<String, Value> = jwt::decode_claims(token)?;
+        let registered: RegisteredClaims = serde_json::from_value(Value::Object(claims.clone()))
+            .map_err(|err| invalid(err.to_string()))?;
+        self.check(&registered, audience, nonce, browser::now() / 1000.0)?;
+
+        Ok(claims)
+    }
+
+    /// The registered claims at `now`, in seconds since the Unix epoch.
+    fn check(
+        &self,
+        claims: &RegisteredClaims,
+        audience: &str,
+        nonce: Option<&str>,
+        now: f64,
+    ) -> Result<(), Auth0Error> {
+        let leeway = f64::from(self.leeway);
+
+        if claims.iss.as_deref() != Some(self.issuer.as_str()) {
+            return Err(invalid(format!(
+                "Issuer (iss) claim mismatch; expected \"{}\", found \"{}\"",
+                self.issuer,
+                claims.iss.as_deref().unwrap_or_default()
+            )));
+        }
+        if !claims.aud.iter().any(|aud| aud == audience) {
+            return Err(invalid(format!("Audience (aud) claim does not include \"{}\"", audience)));
+        }
+        if audience == self.client_id && claims.aud.len() > 1 && claims.azp.as_deref() != Some(audience) {
+            return Err(invalid("Authorized Party (azp) claim mismatch"));
+        }
+        match claims.exp {
+            Some(exp) if exp + leeway >= now => {}
+            Some(_) => return Err(invalid("Expiration Time (exp) claim error; the token has expired")),
+            None => return Err(invalid("Expiration Time (exp) claim must be present")),
+        }
+        if let Some(nbf) = claims.nbf {
+            if nbf - leeway > now {
+                return Err(invalid("Not Before Time (nbf) claim error; the token is not valid yet"));
+            }
+        }
+        if let Some(nonce) = nonce {
+            if claims.nonce.as_deref() != Some(nonce) {
+                return Err(invalid("Nonce (nonce) claim mismatch"));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn key(&self, kid: &str) -> Result<RsaPublicKey, Auth0Error> {
+        if let Some(key) = self.keys.borrow().get(kid) {
+            return Ok(key.clone());
+        }
+
+        // Unknown kid: the keys may have been rotated since they were fetched.
+        let now = browser::now() / 1000.0;
+        if !self.may_fetch(now) {
+            return Err(no_key(kid));
+        }
+        let jwks: JwkSet = browser::get_json(&self.jwks_uri).await?;
+        self.fetched_at.set(Some(now));
+        let mut keys = self.keys.borrow_mut();
+        keys.clear();
+        for jwk in jwks.keys {
+            if let (Some(kid), Some(key)) = (jwk.kid.clone(), rsa_key(&jwk)) {
+                keys.insert(kid, key);
+            }
+        }
+
+        keys.get(kid).cloned().ok_or_else(|| no_key(kid))
+    }
+
+    fn may_fetch(&self, now: f64) -> bool {
+        self.fetched_at
+            .get()
+            .is_none_or(|fetched_at| now - fetched_at >= REFETCH_COOLDOWN)
+    }
+}
+
+fn rsa_key(jwk: &Jwk) -> Option<RsaPublicKey> {
+    if jwk.kty != "RSA" {
+        return None;
+    }
+    let n = jwt::decode_base64(jwk.n.as_deref()?).ok()?;
+    let e = jwt::decode_base64(jwk.e.as_deref()?).ok()?;
+
+    RsaPublicKey::new(BigUint::from_bytes_be(&n), BigUint::from_bytes_be(&e)).ok()
+}
+
+fn no_key(kid: &str) -> Auth0Error {
+    invalid(format!("No signing key found for kid \"{}\"", kid))
+}
+
+fn invalid(description: impl Into<String>) -> Auth0Error {
+    Auth0Error::InvalidToken(description.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: f64 = 1_700_000_000.0;
+
+    fn verifier() -> JwksVerifier {
+        JwksVerifier::new(&ConfigOptions::builder("tenant.eu.auth0.com", "client").build())
+    }
+
+    fn claims(aud: &[&str]) -> RegisteredClaims {
+        RegisteredClaims {
+            iss: Some("https://tenant.eu.auth0.com/".to_string()),
+            aud: aud.iter().map(|aud| aud.to_string()).collect(),
+            exp: Some(NOW + 3600.0),
+            nbf: None,
+            nonce: None,
+            azp: None,
+        }
+    }
+
+    fn error(result: Result<(), Auth0Error>) -> String {
+        match result {
+            Err(Auth0Error::InvalidToken(description)) => description,
+            other => panic!("expected invalid_token, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_claims() {
+        let verifier = verifier();
+
+        assert_eq!(verifier.check(&claims(&["client"]), "client", None, NOW), Ok(()));
+        let access = claims(&["https://api.example.com", "https://tenant.eu.auth0.com/userinfo"]);
+        assert_eq!(verifier.check(&access, "https://api.example.com", None, NOW), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_wrong_issuer() {
+        let claims = RegisteredClaims {
+            iss: Some("https://evil.example.com/".to_string()),
+            ..claims(&["client"])
+        };
+
+        assert!(error(verifier().check(&claims, "client", None, NOW)).contains("(iss)"));
+    }
+
+    #[test]
+    fn rejects_another_audience() {
+        let claims = claims(&["https://other.example.com"]);
+
+        assert!(error(verifier().check(&claims, "https://api.example.com", None, NOW)).contains("(aud)"));
+    }
+
+    #[test]
+    fn requires_azp_for_an_id_token_with_several_audiences() {
+        let verifier = verifier();
+        let without_azp = claims(&["client", "https://api.example.com"]);
+        assert!(error(verifier.check(&without_azp, "client", None, NOW)).contains("(azp)"));
+
+        let with_azp = RegisteredClaims {
+            azp: Some("client".to_string()),
+            ..claims(&["client", "https://api.example.com"])
+        };
+        assert_eq!(verifier.check(&with_azp, "client", None, NOW), Ok(()));
+    }
+
+    #[test]
+    fn allows_an_expiry_within_the_leeway() {
+        let verifier = verifier();
+        let expired = |exp: f64| RegisteredClaims {
+            exp: Some(exp),
+            ..claims(&["client"])
+        };
+
+        assert_eq!(verifier.check(&expired(NOW - 60.0), "client", None, NOW), Ok(()));
+        assert!(error(verifier.check(&expired(NOW - 61.0), "client", None, NOW)).contains("(exp)"));
+        let without_exp = RegisteredClaims {
+            exp: None,
+            ..claims(&["client"])
+        };
+        assert!(error(verifier.check(&without_exp, "client", None, NOW)).contains("(exp)"));
+    }
+
+    #[test]
+    fn rejects_a_token_that_is_not_valid_yet() {
+        let claims = RegisteredClaims {
+            nbf: Some(NOW + 120.0),
+            ..claims(&["client"])
+        };
+
+        assert!(error(verifier().check(&claims, "client", None, NOW)).contains("(nbf)"));
+    }
+
+    #[test]
+    fn checks_the_nonce_when_given() {
+        let verifier = verifier();
+        let claims = RegisteredClaims {
+            nonce: Some("nonce".to_string()),
+            ..claims(&["client"])
+        };
+
+        assert_eq!(verifier.check(&claims, "client", Some("nonce"), NOW), Ok(()));
+        assert_eq!(verifier.check(&claims, "client", None, NOW), Ok(()));
+        assert!(error(verifier.check(&claims, "client", Some("other"), NOW)).contains("(nonce)"));
+    }
+
+    #[test]
+    fn fetches_the_key_set_again_only_after_the_cooldown() {
+        let verifier = verifier();
+        assert!(verifier.may_fetch(NOW));
+
+        verifier.fetched_at.set(Some(NOW));
+        assert!(!verifier.may_fetch(NOW));
+        assert!(!verifier.may_fetch(NOW + REFETCH_COOLDOWN - 1.0));
+        assert!(verifier.may_fetch(NOW + REFETCH_COOLDOWN));
+    }
+}
@@ -0,0 +1,188 @@
+//! Decoding of JWT headers and claims. Signatures are not checked here.
+
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+
+use crate::Auth0Error;
+
+/// The JOSE header of a JWT.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Header {
+    pub alg: String,
+    pub typ: Option<String>,
+    pub kid: Option<String>,
+}
+
+/// A decoded, unverified JWT.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Jwt<C = Map<String, Value>> {
+    pub header: Header,
+    pub claims: C,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum JwtError {
+    /// The token is not made of three `.` separated segments.
+    Malformed,
+    /// A segment is not valid base64url.
+    Base64(String),
+    /// The header or the claims are not the expected JSON.
+    Json(String),
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtError::Malformed => write!(f, "Invalid token specified: expected three segments"),
+            JwtError::Base64(err) => write!(f, "Invalid token specified: {}", err),
+            JwtError::Json(err) => write!(f, "Invalid token specified: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+impl From<JwtError> for Auth0Error {
+    fn from(err: JwtError) -> Self {
+        Auth0Error::InvalidToken(err.to_string())
+    }
+}
+
+/// Decodes both the header and the claims of `token`.
+pub fn decode<C: DeserializeOwned>(token: &str) -> Result<Jwt<C>, JwtError> {
+    let (header, claims, _) = segments(token)?;
+
+    Ok(Jwt {
+        header: decode_segment(header)?,
+        claims: decode_segment(claims)?,
+    })
+}
+
+pub fn decode_header(token: &str) -> Result<Header, JwtError> {
+    let (header, _, _) = segments(token)?;
+    decode_segment(header)
+}
+
+pub fn decode_claims<C: DeserializeOwned>(token: &str) -> Result<C, JwtError> {
+    let (_, claims, _) = segments(token)?;
+    decode_segment(claims)
+}
+
+/// The `header.claims` input the signature was computed over, and the decoded signature.
+#[cfg(feature = "jwks")]
+pub(crate) fn signed_parts(token: &str) -> Result<(&str, Vec<u8>), JwtError> {
+    let (_, _, signature) = segments(token)?;
+    let signing_input = &token[..token.len() - signature.len() - 1];
+
+    Ok((signing_input, decode_base64(signature)?))
+}
+
+fn segments(token: &str) -> Result<(&str, &str, &str), JwtError> {
+    let mut parts = token.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(claims), Some(signature), None) => Ok((header, claims, signature)),
+        _ => Err(JwtError::Malformed),
+    }
+}
+
+fn decode_segment<T: DeserializeOwned>(segment: &str) -> Result<T, JwtError> {
+    let bytes = decode_base64(segment)?;
+    serde_json::from_slice(&bytes).map_err(|err| JwtError::Json(err.to_string()))
+}
+
+pub(crate) fn decode_base64(segment: &str) -> Result<Vec<u8>, JwtError> {
+    base64::decode_config(segment.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .map_err(|err| JwtError::Base64(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Claims {
+        sub: String,
+        name: String,
+    }
+
+    fn segment(json: &str) -> String {
+        base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+    }
+
+    fn token(header: &str, claims: &str) -> String {
+        format!("{}.{}.c2lnbmF0dXJl", segment(header), segment(claims))
+    }
+
+    #[test]
+    fn decodes_header_and_claims() {
+        let jwt: Jwt<Claims> = decode(&token(
+            r#"{"alg":"RS256","typ":"JWT","kid":"key-1"}"#,
+            r#"{"sub":"auth0|1","name":"Jane"}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(jwt.header.alg, "RS256");
+        assert_eq!(jwt.header.kid.as_deref(), Some("key-1"));
+        assert_eq!(jwt.claims.sub, "auth0|1");
+    }
+
+    #[test]
+    fn rejects_a_token_without_three_segments() {
+        let claims = segment(r#"{"sub":"auth0|1"}"#);
+
+        assert_eq!(decode_header(&format!("{}.{}", segment("{}"), claims)), Err(JwtError::Malformed));
+        assert_eq!(decode_header(&format!("a.{}.c.d", claims)), Err(JwtError::Malformed));
+        assert_eq!(decode_header(""), Err(JwtError::Malformed));
+    }
+
+    #[test]
+    fn rejects_invalid_base64url() {
+        let result = decode_claims::<Map<String, Value>>(&format!("{}.not*base64.sig", segment("{}")));
+
+        assert!(matches!(result, Err(JwtError::Base64(_))));
+    }
+
+    #[test]
+    fn rejects_claims_that_are_not_json() {
+        let result = decode_claims::<Map<String, Value>>(&token(r#"{"alg":"RS256"}"#, "not json"));
+
+        assert!(matches!(result, Err(JwtError::Json(_))));
+    }
+
+    #[test]
+    fn rejects_claims_of_the_wrong_shape() {
+        let result = decode_claims::<Claims>(&token(r#"{"alg":"RS256"}"#, r#"{"sub":1}"#));
+
+        assert!(matches!(result, Err(JwtError::Json(_))));
+    }
+
+    #[test]
+    fn accepts_padded_segments() {
+        let header = base64::encode_config(r#"{"alg":"RS256"}"#, base64::URL_SAFE);
+        let claims = base64::encode_config(r#"{"sub":"auth0|12","name":"Joe"}"#, base64::URL_SAFE);
+        assert!(claims.ends_with('='));
+
+        let claims: Claims = decode_claims(&format!("{}.{}.sig", header, claims)).unwrap();
+
+        assert_eq!(claims.name, "Joe");
+    }
+
+    #[test]
+    fn decodes_utf8_claims() {
+        let claims: Claims =
+            decode_claims(&token(r#"{"alg":"RS256"}"#, r#"{"sub":"auth0|1","name":"Zoë Šimić 山田"}"#)).unwrap();
+
+        assert_eq!(claims.name, "Zoë Šimić 山田");
+    }
+
+    #[test]
+    fn maps_to_an_invalid_token_error() {
+        let err = Auth0Error::from(JwtError::Malformed);
+
+        assert_eq!(err.error(), "invalid_token");
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_sys::Storage;
+
+use crate::{browser, sync};
+
+/// Time for a concurrent write by another tab to land before the lease is read back.
+const SETTLE_MILLIS: i32 = 50;
+const POLL_MILLIS: i32 = 100;
+
+#[derive(Serialize, Deserialize)]
+struct Holder {
+    owner: String,
+    /// Milliseconds since the Unix epoch.
+    expires_at: f64,
+}
+
+/// A lease in `localStorage` that at most one tab of the origin holds at a time. `Web Locks` would be the
+/// better primitive, but it is not available in every browser we support.
+pub(crate) struct Lease {
+    key: String,
+    duration: Duration,
+    storage: Option<Storage>,
+}
+
+impl Lease {
+    pub fn new(key: String, duration: Duration) -> Self {
+        let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+        Lease { key, duration, storage }
+    }
+
+    /// Whether this tab got the lease. Without `localStorage` every tab is on its own and always gets it.
+    pub async fn acquire(&self) -> bool {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return true,
+        };
+        if self.held_by_other(storage) {
+            return false;
+        }
+
+        let holder = Holder {
+            owner: sync::tab_id(),
+            expires_at: browser::now() + self.duration.as_millis() as f64,
+        };
+        let written = serde_json::to_string(&holder)
+            .ok()
+            .and_then(|value| storage.set_item(&self.key, &value).ok());
+        if written.is_none() {
+            return true;
+        }
+
+        // Two tabs may have written at the same time, the last write wins.
+        if browser::sleep(SETTLE_MILLIS).await.is_err() {
+            return true;
+        }
+        self.holder(storage).is_some_and(|holder| holder.owner == sync::tab_id())
+    }
+
+    pub fn release(&self) {
+        if let Some(storage) = &self.storage {
+            if self.holder(storage).is_some_and(|holder| holder.owner == sync::tab_id()) {
+                let _ = storage.remove_item(&self.key);
+            }
+        }
+    }
+
+    /// Resolves once no other tab holds the lease, because it was released or has expired.
+    pub async fn wait_for_release(&self) {
+        if let Some(storage) = &self.storage {
+            while self.held_by_other(storage) {
+                if browser::sleep(POLL_MILLIS).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn held_by_other(&self, storage: &Storage) -> bool {
+        self.holder(storage)
+            .is_some_and(|holder| holder.owner != sync::tab_id() && holder.expires_at > browser::now())
+    }
+
+    fn holder(&self, storage: &Storage) -> Option<Holder> {
+        let value = storage.get_item(&self.key).ok().flatten()?;
+        serde_json::from_str(&value).ok()
+    }
+}
@@ -1,17 +1,45 @@
 use wasm_bindgen::prelude::*;
 
+mod browser;
+pub mod cache;
+mod client;
+#[cfg(all(feature = "device", not(target_arch = "wasm32")))]
+pub mod device;
+mod error;
+#[cfg(feature = "jwks")]
+mod jwks;
+pub mod jwt;
+mod lease;
+mod loader;
+mod model;
+#[cfg(feature = "pkce")]
+pub mod pkce;
+mod redirect;
+mod refresh;
+mod service;
+mod sync;
 #[cfg(feature = "auth0-yew")]
 mod auth_yew;
-#[cfg(feature = "auth0-yew")]
+
+pub use error::Auth0Error;
+pub use loader::{load_auth0_spa_js, Auth0SpaJs, AUTH0_SPA_JS_CDN};
+#[cfg(feature = "jwks")]
+pub use jwks::JwksVerifier;
+pub use refresh::{RefreshEvent, RefreshOptions, RefreshScheduler};
+pub use service::Auth0Service;
+pub use sync::{AuthChannel, AuthEvent};
+pub use model::{
+    Address, AdvancedOptions, AuthLogoutOptions, CacheLocation, ConfigOptions, ConfigOptionsBuilder, IdToken, RedirectLoginResult,
+    TokenOptions, TokenResponse, User,
+};
+#[cfg(feature = "auth0-yew-agent")]
 pub use auth_yew::permissions;
-// #[cfg(feature = "auth0-yew")]
-// pub use auth_yew::permissions::PermissionsService;
-#[cfg(feature = "auth0-yew")]
-pub use auth_yew::{Auth0Service, AUTH0_SERVICE};
-#[cfg(feature = "auth0-yew")]
-pub use auth_yew::{Claim, ConfigOptions, User};
 #[cfg(feature = "auth0-yew")]
-pub use auth_yew::{AUTH0_DOMAIN, AUTH0_CLIENT_ID, AUTH0_REDIRECT_URI, AUTH0_USE_REFRESH_TOKENS, AUTH0_CACHE_LOCATION};
+pub use auth_yew::{
+    hooks, Auth0Callbacks, Auth0Provider, Auth0ProviderProps, Auth0ServiceProvider, Auth0ServiceProviderProps,
+    Authorized, AuthorizedProps, ProtectedRoute, ProtectedRouteProps, RequirePermission, RequirePermissionProps,
+    Unauthenticated, UnauthenticatedProps,
+};
 
 #[wasm_bindgen]
 extern "C" {
@@ -21,45 +49,39 @@ extern "C" {
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = Auth0ClientOptions)]
     pub type Auth0ClientOptions;
     #[wasm_bindgen(constructor)]
-    pub fn new(options: Auth0ClientOptions) -> Auth0Client;
-
-    #[wasm_bindgen(extends = :: js_sys :: Object , js_name = CacheLocation)]
-    pub type CacheLocation;
-    #[wasm_bindgen(method, getter = cacheLocation)]
-    pub fn cache_location(this: &Auth0Client) -> CacheLocation;
-    #[wasm_bindgen(method, setter = cacheLocation)]
-    pub fn set_cache_location(this: &Auth0Client, value: CacheLocation);
+    fn new(options: Auth0ClientOptions) -> Auth0Client;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = RedirectLoginOptions)]
     pub type RedirectLoginOptions;
-    #[wasm_bindgen(method, js_name = buildAuthorizeUrl)] // Return String
-    pub async fn build_authorize_url(
+    #[wasm_bindgen(method, catch, js_name = buildAuthorizeUrl)]
+    async fn js_build_authorize_url(
         this: &Auth0Client,
         options: Option<RedirectLoginOptions>,
-    ) -> JsValue;
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = LogoutUrlOptions)]
     pub type LogoutUrlOptions;
-    #[wasm_bindgen(method, js_name = buildLogoutUrl)]
-    pub fn build_logout_url(this: &Auth0Client, options: LogoutUrlOptions) -> String;
+    #[wasm_bindgen(method, catch, js_name = buildLogoutUrl)]
+    fn js_build_logout_url(this: &Auth0Client, options: Option<LogoutUrlOptions>) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = GetTokenSilentlyOptions)]
     pub type GetTokenSilentlyOptions;
-    #[wasm_bindgen(method, js_name = CheckSession)]
-    pub async fn check_session(this: &Auth0Client, options: Option<GetTokenSilentlyOptions>);
+    #[wasm_bindgen(method, catch, js_name = checkSession)]
+    async fn js_check_session(
+        this: &Auth0Client,
+        options: Option<GetTokenSilentlyOptions>,
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = GetIdTokenClaimsOptions)]
     pub type GetIdTokenClaimsOptions;
-    #[wasm_bindgen(extends = :: js_sys :: Object , js_name = IdToken)]
-    pub type IdToken;
-    #[wasm_bindgen(method, js_name = getIdTokenClaims)] // Return IdToken
-    pub async fn get_id_token_claims(
+    #[wasm_bindgen(method, catch, js_name = getIdTokenClaims)]
+    async fn js_get_id_token_claims(
         this: &Auth0Client,
         options: Option<GetIdTokenClaimsOptions>,
-    ) -> JsValue;
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, catch, js_name = getTokenSilently)]
-    pub async fn get_token_silently(
+    async fn js_get_token_silently(
         this: &Auth0Client,
         options: Option<GetTokenSilentlyOptions>,
     ) -> Result<JsValue, JsValue>;
@@ -68,43 +90,41 @@ extern "C" {
     pub type GetTokenWithPopupOptions;
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = PopupConfigOptions)]
     pub type PopupConfigOptions;
-    #[wasm_bindgen(method, js_name = getTokenWithPopup)] // Return String
-    pub async fn get_token_with_popup(
+    #[wasm_bindgen(method, catch, js_name = getTokenWithPopup)]
+    async fn js_get_token_with_popup(
         this: &Auth0Client,
         options: Option<GetTokenWithPopupOptions>,
         config: Option<PopupConfigOptions>,
-    ) -> JsValue;
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = GetUserOptions)]
     pub type GetUserOptions;
-    #[wasm_bindgen(method, js_name = getUser)]
-    pub async fn get_user(this: &Auth0Client, options: Option<GetUserOptions>) -> JsValue;
+    #[wasm_bindgen(method, catch, js_name = getUser)]
+    async fn js_get_user(this: &Auth0Client, options: Option<GetUserOptions>) -> Result<JsValue, JsValue>;
 
-    #[wasm_bindgen(extends = :: js_sys :: Object , js_name = RedirectLoginResult)]
-    pub type RedirectLoginResult;
-    #[wasm_bindgen(method, catch, js_name = handleRedirectCallback)] // Return RedirectLoginResult
-    pub async fn handle_redirect_callback(this: &Auth0Client, url: Option<String>) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(method, catch, js_name = handleRedirectCallback)]
+    async fn js_handle_redirect_callback(this: &Auth0Client, url: Option<String>) -> Result<JsValue, JsValue>;
 
-    #[wasm_bindgen(method, js_name = isAuthenticated)] // Return bool
-    pub async fn is_authenticated(this: &Auth0Client) -> JsValue;
+    #[wasm_bindgen(method, catch, js_name = isAuthenticated)]
+    async fn js_is_authenticated(this: &Auth0Client) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = PopupLoginOptions)]
     pub type PopupLoginOptions;
-    #[wasm_bindgen(method, js_name = loginWithPopup)]
-    pub async fn login_with_popup(
+    #[wasm_bindgen(method, catch, js_name = loginWithPopup)]
+    async fn js_login_with_popup(
         this: &Auth0Client,
         options: Option<PopupLoginOptions>,
         config: Option<PopupConfigOptions>,
-    );
+    ) -> Result<JsValue, JsValue>;
 
-    #[wasm_bindgen(method, js_name = loginWithRedirect)]
-    pub async fn login_with_redirect(this: &Auth0Client, options: Option<RedirectLoginOptions>);
+    #[wasm_bindgen(method, catch, js_name = loginWithRedirect)]
+    async fn js_login_with_redirect(
+        this: &Auth0Client,
+        options: Option<RedirectLoginOptions>,
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(extends = :: js_sys :: Object , js_name = LogoutOptions)]
     pub type LogoutOptions;
-    #[wasm_bindgen(method)]
-    pub fn logout(this: &Auth0Client, options: Option<LogoutOptions>);
+    #[wasm_bindgen(method, catch, js_name = logout)]
+    fn js_logout(this: &Auth0Client, options: Option<LogoutOptions>) -> Result<JsValue, JsValue>;
 }
-
-unsafe impl Sync for Auth0Client {}
-unsafe impl Send for Auth0Client {}
@@ -0,0 +1,107 @@
+use std::{cell::Cell, rc::Rc};
+
+use js_sys::Reflect;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{HtmlElement, Window};
+
+use crate::{browser, Auth0Client, Auth0ClientOptions, Auth0Error};
+
+/// The production build on Auth0's CDN.
+pub const AUTH0_SPA_JS_CDN: &str = "https://cdn.auth0.com/js/auth0-spa-js/1.22/auth0-spa-js.production.js";
+
+const SCRIPT_ID: &str = "auth0-spa-js";
+const LOAD_TIMEOUT_IN_SECONDS: u32 = 10;
+
+/// Proof that the `auth0-spa-js` global is defined. Returned by [`load_auth0_spa_js`] and needed for anything
+/// that constructs an `Auth0Client`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Auth0SpaJs {
+    _private: (),
+}
+
+impl Auth0SpaJs {
+    /// For pages that load the script themselves: `Some` once the global is defined.
+    pub fn detect() -> Option<Self> {
+        let window = web_sys::window()?;
+        is_defined(&window).then_some(Auth0SpaJs { _private: () })
+    }
+
+    pub fn client(&self, options: Auth0ClientOptions) -> Auth0Client {
+        Auth0Client::new(options)
+    }
+}
+
+/// Injects `auth0-spa-js` from `src` unless it's already on the page and resolves once the global is defined.
+/// `integrity` is an optional SRI hash, e.g. `sha384-...`. A script that fails to load is removed again, so a
+/// later call retries.
+pub async fn load_auth0_spa_js(src: &str, integrity: Option<&str>) -> Result<Auth0SpaJs, Auth0Error> {
+    let window = browser::window()?;
+    if let Some(loaded) = Auth0SpaJs::detect() {
+        return Ok(loaded);
+    }
+
+    let document = window
+        .document()
+        .ok_or_else(|| Auth0Error::Js("document is not available".to_string()))?;
+    let script: HtmlElement = match document.get_element_by_id(SCRIPT_ID) {
+        Some(script) => script.unchecked_into(),
+        None => {
+            let script: HtmlElement = document.create_element("script")?.unchecked_into();
+            script.set_id(SCRIPT_ID);
+            script.set_attribute("src", src)?;
+            if let Some(integrity) = integrity {
+                script.set_attribute("integrity", integrity)?;
+                script.set_attribute("crossorigin", "anonymous")?;
+            }
+            document
+                .head()
+                .ok_or_else(|| Auth0Error::Js("document has no head".to_string()))?
+                .append_child(&script)?;
+            script
+        }
+    };
+
+    let failed = Rc::new(Cell::new(false));
+    let on_error = {
+        let failed = failed.clone();
+        Closure::wrap(Box::new(move || failed.set(true)) as Box<dyn FnMut()>)
+    };
+    script.add_event_listener_with_callback("error", on_error.as_ref().unchecked_ref())?;
+
+    let deadline = browser::now() + f64::from(LOAD_TIMEOUT_IN_SECONDS) * 1000.0;
+    let result = loop {
+        if is_defined(&window) {
+            break Ok(Auth0SpaJs { _private: () });
+        }
+        if failed.get() {
+            script.remove();
+            break Err(Auth0Error::LoadFailed(format!("Failed to load {}", src)));
+        }
+        if browser::now() > deadline {
+            break Err(Auth0Error::Timeout(format!("{} did not load within {}s", src, LOAD_TIMEOUT_IN_SECONDS)));
+        }
+        if let Err(err) = browser::sleep(50).await {
+            break Err(err);
+        }
+    };
+
+    script.remove_event_listener_with_callback("error", on_error.as_ref().unchecked_ref())?;
+    result
+}
+
+fn is_defined(window: &Window) -> bool {
+    let defined = |target: &JsValue, key: &str| {
+        Reflect::get(target, &JsValue::from_str(key))
+            .ok()
+            .filter(|value| !value.is_undefined())
+    };
+    if defined(window, "Auth0Client").is_some() {
+        return true;
+    }
+
+    // The UMD bundle only defines `createAuth0Client` and hangs the class off it.
+    match defined(window, "createAuth0Client").and_then(|create| defined(&create, "Auth0Client")) {
+        Some(class) => Reflect::set(window, &JsValue::from_str("Auth0Client"), &class).unwrap_or(false),
+        None => false,
+    }
+}
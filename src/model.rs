@@ -0,0 +1,437 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    cache::{CacheHandle, TokenCache},
+    jwt, Auth0ClientOptions, Auth0Error, GetTokenSilentlyOptions, LogoutOptions, LogoutUrlOptions,
+};
+
+#[cfg(any(feature = "pkce", feature = "device"))]
+const DEFAULT_SCOPE: &str = "openid profile email";
+
+/// OIDC user profile. Claims outside the standard set are deserialized into `Extra`.
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct User<Extra = serde_json::Map<String, serde_json::Value>> {
+    pub sub: Option<String>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub nickname: Option<String>,
+    pub preferred_username: Option<String>,
+    pub profile: Option<String>,
+    pub picture: Option<String>,
+    pub website: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub gender: Option<String>,
+    pub birthdate: Option<String>,
+    pub zoneinfo: Option<String>,
+    pub locale: Option<String>,
+    pub phone_number: Option<String>,
+    pub phone_number_verified: Option<bool>,
+    pub address: Option<Address>,
+    pub updated_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Address {
+    pub formatted: Option<String>,
+    pub street_address: Option<String>,
+    pub locality: Option<String>,
+    pub region: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+}
+
+/// Claims of the ID token. Custom claims, e.g. namespaced ones added by Auth0 Actions, are deserialized into `T`.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct IdToken<T = serde_json::Map<String, serde_json::Value>> {
+    pub __raw: String,
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub aud: Vec<String>,
+    pub exp: Option<u64>,
+    pub iat: Option<u64>,
+    pub auth_time: Option<u64>,
+    pub nonce: Option<String>,
+    pub acr: Option<String>,
+    pub amr: Option<Vec<String>>,
+    pub azp: Option<String>,
+    pub org_id: Option<String>,
+    #[serde(flatten)]
+    pub custom: T,
+}
+
+pub(crate) fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(value)) => vec![value],
+        Some(OneOrMany::Many(values)) => values,
+        None => Vec::new(),
+    })
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct RedirectLoginResult {
+    #[serde(rename = "appState", default)]
+    pub app_state: Option<serde_json::Value>,
+}
+
+impl RedirectLoginResult {
+    /// Deserializes the `appState` passed to `login_with_redirect`.
+    pub fn app_state<T: DeserializeOwned>(&self) -> Option<T> {
+        self.app_state
+            .clone()
+            .and_then(|app_state| serde_json::from_value(app_state).ok())
+    }
+}
+
+/// Detailed result of `getTokenSilently`.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub id_token: String,
+    /// Lifetime of the access token in seconds.
+    pub expires_in: u64,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl TokenResponse {
+    /// The scopes granted to the access token, which may be fewer than the ones requested.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope.as_deref().unwrap_or_default().split_whitespace()
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|granted| granted == scope)
+    }
+
+    /// The profile in the ID token. The token is only decoded, not verified.
+    pub fn user<Extra: DeserializeOwned>(&self) -> Result<User<Extra>, Auth0Error> {
+        Ok(jwt::decode_claims(&self.id_token)?)
+    }
+
+    pub fn id_token_claims<T: DeserializeOwned>(&self) -> Result<IdToken<T>, Auth0Error> {
+        let mut claims: serde_json::Map<String, serde_json::Value> = jwt::decode_claims(&self.id_token)?;
+        claims.insert("__raw".to_string(), serde_json::Value::String(self.id_token.clone()));
+
+        serde_json::from_value(serde_json::Value::Object(claims)).map_err(|err| Auth0Error::Deserialize(err.to_string()))
+    }
+}
+
+/// Where `auth0-spa-js` keeps tokens when no [`TokenCache`] is set.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheLocation {
+    Memory,
+    LocalStorage,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct AdvancedOptions {
+    #[serde(rename = "defaultScope", skip_serializing_if = "Option::is_none")]
+    pub default_scope: Option<String>,
+}
+
+/// Options passed to the `Auth0Client` constructor. Use [`ConfigOptions::builder`] to create one.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ConfigOptions {
+    pub(crate) domain: String,
+    pub(crate) client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) redirect_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) audience: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) connection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) organization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_age: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) leeway: Option<u32>,
+    #[serde(rename = "useRefreshTokens", skip_serializing_if = "Option::is_none")]
+    pub(crate) use_refresh_tokens: Option<bool>,
+    #[serde(rename = "cacheLocation", skip_serializing_if = "Option::is_none")]
+    pub(crate) cache_location: Option<CacheLocation>,
+    #[serde(skip)]
+    pub(crate) cache: Option<CacheHandle>,
+    #[serde(rename = "authorizeTimeoutInSeconds", skip_serializing_if = "Option::is_none")]
+    pub(crate) authorize_timeout_in_seconds: Option<u32>,
+    #[serde(rename = "useCookiesForTransactions", skip_serializing_if = "Option::is_none")]
+    pub(crate) use_cookies_for_transactions: Option<bool>,
+    #[serde(rename = "sessionCheckExpiryDays", skip_serializing_if = "Option::is_none")]
+    pub(crate) session_check_expiry_days: Option<u32>,
+    #[serde(rename = "advancedOptions", skip_serializing_if = "Option::is_none")]
+    pub(crate) advanced_options: Option<AdvancedOptions>,
+    /// Any other parameters, sent as-is to the `/authorize` endpoint.
+    #[serde(flatten)]
+    pub(crate) authorize_params: BTreeMap<String, String>,
+}
+
+impl ConfigOptions {
+    pub fn builder(domain: impl Into<String>, client_id: impl Into<String>) -> ConfigOptionsBuilder {
+        ConfigOptionsBuilder {
+            options: ConfigOptions {
+                domain: domain.into(),
+                client_id: client_id.into(),
+                redirect_uri: None,
+                audience: None,
+                scope: None,
+                connection: None,
+                organization: None,
+                issuer: None,
+                max_age: None,
+                leeway: None,
+                use_refresh_tokens: None,
+                cache_location: None,
+                cache: None,
+                authorize_timeout_in_seconds: None,
+                use_cookies_for_transactions: None,
+                session_check_expiry_days: None,
+                advanced_options: None,
+                authorize_params: BTreeMap::new(),
+            },
+        }
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    pub fn redirect_uri(&self) -> Option<&str> {
+        self.redirect_uri.as_deref()
+    }
+
+    /// The expected `iss` claim: `issuer`, or else the domain, as an `https://` URL with a trailing slash.
+    #[cfg(any(feature = "pkce", feature = "jwks"))]
+    pub(crate) fn issuer_url(&self) -> String {
+        let issuer = self.issuer.as_deref().unwrap_or(&self.domain);
+        if issuer.starts_with("https://") {
+            format!("{}/", issuer.trim_end_matches('/'))
+        } else {
+            format!("https://{}/", issuer)
+        }
+    }
+
+    /// `openid`, the default scope, the configured scope and `scope`, without duplicates.
+    #[cfg(any(feature = "pkce", feature = "device"))]
+    pub(crate) fn merged_scope(&self, scope: Option<&str>) -> String {
+        let default_scope = self
+            .advanced_options
+            .as_ref()
+            .and_then(|options| options.default_scope.as_deref())
+            .unwrap_or(DEFAULT_SCOPE);
+        let offline_access = match self.use_refresh_tokens {
+            Some(true) => Some("offline_access"),
+            _ => None,
+        };
+
+        let mut scopes: Vec<&str> = Vec::new();
+        let requested = [Some("openid"), Some(default_scope), self.scope.as_deref(), scope, offline_access];
+        for value in requested.iter().flatten().flat_map(|value| value.split_whitespace()) {
+            if !scopes.contains(&value) {
+                scopes.push(value);
+            }
+        }
+        scopes.join(" ")
+    }
+}
+
+impl From<&ConfigOptions> for Auth0ClientOptions {
+    fn from(options: &ConfigOptions) -> Self {
+        let js_options = JsValue::from_serde(options).unwrap();
+        if let Some(cache) = &options.cache {
+            let _ = js_sys::Reflect::set(&js_options, &JsValue::from_str("cache"), &cache.to_js());
+        }
+        js_options.into()
+    }
+}
+
+pub struct ConfigOptionsBuilder {
+    options: ConfigOptions,
+}
+
+impl ConfigOptionsBuilder {
+    pub fn redirect_uri(mut self, redirect_uri: impl Into<String>) -> Self {
+        self.options.redirect_uri = Some(redirect_uri.into());
+        self
+    }
+
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.options.audience = Some(audience.into());
+        self
+    }
+
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.options.scope = Some(scope.into());
+        self
+    }
+
+    pub fn connection(mut self, connection: impl Into<String>) -> Self {
+        self.options.connection = Some(connection.into());
+        self
+    }
+
+    pub fn organization(mut self, organization: impl Into<String>) -> Self {
+        self.options.organization = Some(organization.into());
+        self
+    }
+
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.options.issuer = Some(issuer.into());
+        self
+    }
+
+    pub fn max_age(mut self, seconds: u32) -> Self {
+        self.options.max_age = Some(seconds);
+        self
+    }
+
+    pub fn leeway(mut self, seconds: u32) -> Self {
+        self.options.leeway = Some(seconds);
+        self
+    }
+
+    pub fn use_refresh_tokens(mut self, use_refresh_tokens: bool) -> Self {
+        self.options.use_refresh_tokens = Some(use_refresh_tokens);
+        self
+    }
+
+    pub fn cache_location(mut self, cache_location: CacheLocation) -> Self {
+        self.options.cache_location = Some(cache_location);
+        self
+    }
+
+    /// Keeps tokens in `cache` instead of the built-in `cache_location` storage. The cache is left out when comparing
+    /// options, replacing it with another one does not make them unequal.
+    pub fn cache(mut self, cache: impl TokenCache + 'static) -> Self {
+        self.options.cache = Some(CacheHandle(Rc::new(cache)));
+        self
+    }
+
+    pub fn authorize_timeout_in_seconds(mut self, seconds: u32) -> Self {
+        self.options.authorize_timeout_in_seconds = Some(seconds);
+        self
+    }
+
+    pub fn use_cookies_for_transactions(mut self, use_cookies: bool) -> Self {
+        self.options.use_cookies_for_transactions = Some(use_cookies);
+        self
+    }
+
+    pub fn session_check_expiry_days(mut self, days: u32) -> Self {
+        self.options.session_check_expiry_days = Some(days);
+        self
+    }
+
+    pub fn default_scope(mut self, default_scope: impl Into<String>) -> Self {
+        self.options.advanced_options = Some(AdvancedOptions {
+            default_scope: Some(default_scope.into()),
+        });
+        self
+    }
+
+    /// Adds an extra parameter for the `/authorize` endpoint, e.g. `screen_hint` or `ui_locales`.
+    pub fn authorize_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.authorize_params.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> ConfigOptions {
+        self.options
+    }
+}
+
+/// Per-call options for `getTokenSilently`. Fields left as `None` fall back to the values in [`ConfigOptions`].
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct TokenOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(rename = "ignoreCache")]
+    pub ignore_cache: bool,
+    #[serde(rename = "timeoutInSeconds", skip_serializing_if = "Option::is_none")]
+    pub timeout_in_seconds: Option<u32>,
+}
+
+impl TokenOptions {
+    pub fn audience(audience: impl Into<String>) -> Self {
+        TokenOptions {
+            audience: Some(audience.into()),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&TokenOptions> for GetTokenSilentlyOptions {
+    fn from(options: &TokenOptions) -> Self {
+        JsValue::from_serde(options).unwrap().into()
+    }
+}
+
+/// Per-call options for `logout` and `buildLogoutUrl`. `return_to` falls back to the configured redirect URI and
+/// `client_id` to the configured client.
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct AuthLogoutOptions {
+    #[serde(rename = "returnTo", skip_serializing_if = "Option::is_none")]
+    pub return_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Also logs the user out of the identity provider, e.g. an enterprise connection.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub federated: bool,
+    /// Only clears the local session, without redirecting to Auth0. Ignored by `build_logout_url`.
+    #[serde(rename = "localOnly", skip_serializing_if = "std::ops::Not::not")]
+    pub local_only: bool,
+}
+
+impl AuthLogoutOptions {
+    pub fn return_to(return_to: impl Into<String>) -> Self {
+        AuthLogoutOptions {
+            return_to: Some(return_to.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn local_only() -> Self {
+        AuthLogoutOptions {
+            local_only: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&AuthLogoutOptions> for LogoutOptions {
+    fn from(options: &AuthLogoutOptions) -> Self {
+        JsValue::from_serde(options).unwrap().into()
+    }
+}
+
+impl From<&AuthLogoutOptions> for LogoutUrlOptions {
+    fn from(options: &AuthLogoutOptions) -> Self {
+        JsValue::from_serde(options).unwrap().into()
+    }
+}
@@ -0,0 +1,255 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{browser, jwt, model::one_or_many, Auth0Error, ConfigOptions};
+
+const DEFAULT_LEEWAY: u32 = 60;
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: Option<String>,
+    sub: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    aud: Vec<String>,
+    azp: Option<String>,
+    exp: Option<f64>,
+    iat: Option<f64>,
+    nbf: Option<f64>,
+    auth_time: Option<f64>,
+    nonce: Option<String>,
+    org_id: Option<String>,
+}
+
+/// The claim checks auth0-spa-js runs on the ID token of a code exchange, at `now` (seconds since the Unix epoch).
+/// The signature is not verified, the token comes straight from the token endpoint.
+pub(crate) fn validate(token: &str, config: &ConfigOptions, nonce: &str, now: f64) -> Result<(), Auth0Error> {
+    let header = jwt::decode_header(token)?;
+    if header.alg != "RS256" {
+        return Err(invalid(format!(
+            "Signature algorithm of \"{}\" is not supported. Expected the ID token to be signed with \"RS256\"",
+            header.alg
+        )));
+    }
+
+    let claims: Claims = jwt::decode_claims(token)?;
+    let leeway = f64::from(config.leeway.unwrap_or(DEFAULT_LEEWAY));
+
+    let issuer = config.issuer_url();
+    if claims.iss.as_deref() != Some(issuer.as_str()) {
+        return Err(invalid(format!(
+            "Issuer (iss) claim mismatch in the ID token; expected \"{}\", found \"{}\"",
+            issuer,
+            claims.iss.as_deref().unwrap_or_default()
+        )));
+    }
+    if claims.sub.is_none() {
+        return Err(invalid("Subject (sub) claim must be a string present in the ID token"));
+    }
+    if !claims.aud.contains(&config.client_id) {
+        return Err(invalid(format!(
+            "Audience (aud) claim mismatch in the ID token; expected \"{}\"",
+            config.client_id
+        )));
+    }
+    if claims.aud.len() > 1 && claims.azp.as_deref() != Some(config.client_id.as_str()) {
+        return Err(invalid("Authorized Party (azp) claim mismatch in the ID token"));
+    }
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(invalid("Nonce (nonce) claim mismatch in the ID token"));
+    }
+    match claims.exp {
+        Some(exp) if exp + leeway >= now => {}
+        Some(_) => return Err(invalid("Expiration Time (exp) claim error in the ID token")),
+        None => return Err(invalid("Expiration Time (exp) claim must be a number present in the ID token")),
+    }
+    if claims.iat.is_none() {
+        return Err(invalid("Issued At (iat) claim must be a number present in the ID token"));
+    }
+    if let Some(nbf) = claims.nbf {
+        if nbf - leeway > now {
+            return Err(invalid("Not Before Time (nbf) claim error in the ID token; it is not valid yet"));
+        }
+    }
+    if let Some(max_age) = config.max_age {
+        match claims.auth_time {
+            Some(auth_time) if auth_time + f64::from(max_age) + leeway >= now => {}
+            Some(_) => {
+                return Err(invalid(
+                    "Authentication Time (auth_time) claim in the ID token indicates that too much time has passed \
+                     since the last end-user authentication",
+                ))
+            }
+            None => {
+                return Err(invalid(
+                    "Authentication Time (auth_time) claim must be a number present in the ID token when Max Age \
+                     (max_age) is specified",
+                ))
+            }
+        }
+    }
+    if let Some(organization) = &config.organization {
+        if claims.org_id.as_deref() != Some(organization.as_str()) {
+            return Err(invalid(format!(
+                "Organization Id (org_id) claim mismatch in the ID token; expected \"{}\", found \"{}\"",
+                organization,
+                claims.org_id.as_deref().unwrap_or_default()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a stored ID token is past its `exp`, allowing for `leeway`. A token without `exp` counts as expired.
+pub(crate) fn is_expired(payload: &Map<String, Value>, config: &ConfigOptions) -> bool {
+    let leeway = f64::from(config.leeway.unwrap_or(DEFAULT_LEEWAY));
+    payload
+        .get("exp")
+        .and_then(Value::as_f64)
+        .is_none_or(|exp| exp + leeway < browser::now() / 1000.0)
+}
+
+fn invalid(description: impl Into<String>) -> Auth0Error {
+    Auth0Error::InvalidToken(description.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const NOW: f64 = 1_700_000_000.0;
+
+    fn config() -> ConfigOptions {
+        ConfigOptions::builder("tenant.eu.auth0.com", "client").build()
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": "https://tenant.eu.auth0.com/",
+            "sub": "auth0|1",
+            "aud": "client",
+            "nonce": "nonce",
+            "iat": NOW - 10.0,
+            "exp": NOW + 3600.0,
+        })
+    }
+
+    fn token(claims: &Value) -> String {
+        let segment = |json: String| base64::encode_config(json, base64::URL_SAFE_NO_PAD);
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            segment(json!({ "alg": "RS256", "typ": "JWT" }).to_string()),
+            segment(claims.to_string())
+        )
+    }
+
+    fn with(claim: &str, value: Value) -> String {
+        let mut claims = claims();
+        claims[claim] = value;
+        token(&claims)
+    }
+
+    fn error(result: Result<(), Auth0Error>) -> String {
+        match result {
+            Err(Auth0Error::InvalidToken(description)) => description,
+            other => panic!("expected invalid_token, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn accepts_a_valid_token() {
+        assert_eq!(validate(&token(&claims()), &config(), "nonce", NOW), Ok(()));
+    }
+
+    #[test]
+    fn rejects_other_algorithms() {
+        let segment = |json: &str| base64::encode_config(json, base64::URL_SAFE_NO_PAD);
+        let token = format!("{}.{}.", segment(r#"{"alg":"HS256"}"#), segment(&claims().to_string()));
+
+        assert!(error(validate(&token, &config(), "nonce", NOW)).contains("HS256"));
+    }
+
+    #[test]
+    fn rejects_a_wrong_nonce() {
+        assert!(error(validate(&token(&claims()), &config(), "other", NOW)).contains("(nonce)"));
+    }
+
+    #[test]
+    fn rejects_a_wrong_issuer() {
+        let token = with("iss", json!("https://evil.example.com/"));
+
+        assert!(error(validate(&token, &config(), "nonce", NOW)).contains("(iss)"));
+    }
+
+    #[test]
+    fn accepts_a_configured_issuer() {
+        let config = ConfigOptions::builder("tenant.eu.auth0.com", "client")
+            .issuer("login.example.com")
+            .build();
+        let token = with("iss", json!("https://login.example.com/"));
+
+        assert_eq!(validate(&token, &config, "nonce", NOW), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_token_for_another_client() {
+        let token = with("aud", json!("other"));
+
+        assert!(error(validate(&token, &config(), "nonce", NOW)).contains("(aud)"));
+    }
+
+    #[test]
+    fn requires_azp_with_several_audiences() {
+        let without_azp = with("aud", json!(["client", "https://api.example.com"]));
+        assert!(error(validate(&without_azp, &config(), "nonce", NOW)).contains("(azp)"));
+
+        let mut claims = claims();
+        claims["aud"] = json!(["client", "https://api.example.com"]);
+        claims["azp"] = json!("client");
+        assert_eq!(validate(&token(&claims), &config(), "nonce", NOW), Ok(()));
+    }
+
+    #[test]
+    fn allows_an_expiry_within_the_leeway() {
+        let token = with("exp", json!(NOW - 30.0));
+
+        assert_eq!(validate(&token, &config(), "nonce", NOW), Ok(()));
+    }
+
+    #[test]
+    fn rejects_an_expiry_past_the_leeway() {
+        let token = with("exp", json!(NOW - 61.0));
+        assert!(error(validate(&token, &config(), "nonce", NOW)).contains("(exp)"));
+
+        let config = ConfigOptions::builder("tenant.eu.auth0.com", "client").leeway(120).build();
+        assert_eq!(validate(&token, &config, "nonce", NOW), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_token_that_is_not_valid_yet() {
+        let token = with("nbf", json!(NOW + 120.0));
+
+        assert!(error(validate(&token, &config(), "nonce", NOW)).contains("(nbf)"));
+    }
+
+    #[test]
+    fn checks_auth_time_against_max_age() {
+        let config = ConfigOptions::builder("tenant.eu.auth0.com", "client").max_age(300).build();
+
+        assert!(error(validate(&token(&claims()), &config, "nonce", NOW)).contains("(auth_time)"));
+        assert_eq!(validate(&with("auth_time", json!(NOW - 300.0)), &config, "nonce", NOW), Ok(()));
+        assert!(error(validate(&with("auth_time", json!(NOW - 400.0)), &config, "nonce", NOW)).contains("(auth_time)"));
+    }
+
+    #[test]
+    fn checks_the_organization() {
+        let config = ConfigOptions::builder("tenant.eu.auth0.com", "client")
+            .organization("org_1")
+            .build();
+
+        assert!(error(validate(&with("org_id", json!("org_2")), &config, "nonce", NOW)).contains("(org_id)"));
+        assert_eq!(validate(&with("org_id", json!("org_1")), &config, "nonce", NOW), Ok(()));
+    }
+}
@@ -0,0 +1,574 @@
+mod id_token;
+mod store;
+mod web_message;
+
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use web_sys::Element;
+
+use crate::{
+    browser, jwt, Auth0Error, AuthLogoutOptions, CacheLocation, ConfigOptions, IdToken, RedirectLoginResult, TokenOptions,
+    TokenResponse, User,
+};
+
+use self::{
+    store::{CacheEntry, Store, Transaction},
+    web_message::AuthorizationResponse,
+};
+
+const DEFAULT_AUDIENCE: &str = "default";
+const DEFAULT_TIMEOUT_IN_SECONDS: u32 = 60;
+/// Cached access tokens are refreshed this many seconds before they expire.
+const EXPIRY_MARGIN: f64 = 60.0;
+const ID_TOKEN_KEY: &str = "@@user@@";
+
+/// Authorization Code + PKCE flow implemented in Rust. Talks to the tenant's `/authorize` and `/oauth/token`
+/// endpoints directly, so `auth0-spa-js` does not have to be loaded.
+pub struct PkceClient {
+    config: ConfigOptions,
+    store: Store,
+}
+
+#[derive(Serialize)]
+struct CodeExchange<'a> {
+    grant_type: &'static str,
+    client_id: &'a str,
+    code: &'a str,
+    code_verifier: &'a str,
+    redirect_uri: &'a str,
+}
+
+#[derive(Serialize)]
+struct RefreshExchange<'a> {
+    grant_type: &'static str,
+    client_id: &'a str,
+    refresh_token: &'a str,
+}
+
+struct StoredIdToken {
+    raw: String,
+    payload: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    access_token: String,
+    id_token: String,
+    expires_in: f64,
+    scope: Option<String>,
+    refresh_token: Option<String>,
+}
+
+impl PkceClient {
+    pub fn new(config: ConfigOptions) -> Self {
+        let persist = config.cache_location == Some(CacheLocation::LocalStorage);
+        let store = Store::new(&config.client_id, persist);
+
+        PkceClient { config, store }
+    }
+
+    pub fn config(&self) -> &ConfigOptions {
+        &self.config
+    }
+
+    /// Builds the `/authorize` URL for a redirect login and stores the transaction it belongs to.
+    /// `app_state` is handed back by `handle_redirect_callback`.
+    pub fn build_authorize_url(&self, app_state: Option<Value>) -> Result<String, Auth0Error> {
+        let transaction = Transaction {
+            app_state,
+            ..self.transaction(self.audience(None), self.config.merged_scope(None))?
+        };
+        store::save_transaction(&self.config.client_id, &transaction)?;
+
+        Ok(self.authorize_url(&transaction, "query", None))
+    }
+
+    pub fn build_logout_url(&self, options: &AuthLogoutOptions) -> String {
+        let client_id = options.client_id.as_ref().unwrap_or(&self.config.client_id);
+        let mut params = vec![("client_id", client_id.clone())];
+        if let Some(return_to) = &options.return_to {
+            params.push(("returnTo", return_to.clone()));
+        }
+
+        let url = format!("{}/v2/logout?{}", self.origin(), query_string(&params));
+        if options.federated {
+            // A flag without a value, like auth0-spa-js sends it.
+            format!("{}&federated", url)
+        } else {
+            url
+        }
+    }
+
+    pub async fn login_with_redirect(&self, app_state: Option<Value>) -> Result<(), Auth0Error> {
+        let url = self.build_authorize_url(app_state)?;
+        browser::window()?.location().assign(&url)?;
+        Ok(())
+    }
+
+    pub async fn login_with_popup(&self) -> Result<(), Auth0Error> {
+        let transaction = self.transaction(self.audience(None), self.config.merged_scope(None))?;
+        let url = self.authorize_url(&transaction, "web_message", None);
+
+        let popup = browser::window()?
+            .open_with_url_and_target_and_features(
+                &url,
+                "auth0:authorize:popup",
+                "left=100,top=100,width=400,height=600,resizable,scrollbars=yes,status=1",
+            )?
+            .ok_or_else(|| Auth0Error::PopupBlocked("Unable to open a popup for loginWithPopup".to_string()))?;
+
+        let response = web_message::wait_for_response(self.origin(), Some(&popup), self.timeout()).await;
+        let _ = popup.close();
+
+        self.complete(&transaction, response?).await?;
+        Ok(())
+    }
+
+    /// Exchanges the `code` in `url` (defaults to the current location) for tokens.
+    pub async fn handle_redirect_callback(&self, url: Option<String>) -> Result<RedirectLoginResult, Auth0Error> {
+        let url = match url {
+            Some(url) => url,
+            None => browser::window()?.location().href()?,
+        };
+        let params = query_params(&url);
+        let transaction = store::take_transaction(&self.config.client_id)
+            .ok_or_else(|| Auth0Error::InvalidState("Invalid state".to_string()))?;
+
+        if let Some(error) = params.get("error") {
+            let description = params.get("error_description").cloned().unwrap_or_default();
+            return Err(Auth0Error::from_code(error, description));
+        }
+
+        let response = AuthorizationResponse {
+            code: params.get("code").cloned(),
+            state: params.get("state").cloned(),
+        };
+        self.complete(&transaction, response).await?;
+
+        Ok(RedirectLoginResult {
+            app_state: transaction.app_state,
+        })
+    }
+
+    /// Restores the session Auth0 still has for the user with a silent authorization, e.g. after a reload with the
+    /// in-memory store. Nothing is requested while a token is cached. No session is not an error.
+    pub async fn check_session(&self) -> Result<(), Auth0Error> {
+        match self.get_token_response(None).await {
+            Ok(_) => Ok(()),
+            Err(err) if err.requires_login() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn user(&self) -> Result<Option<User>, Auth0Error> {
+        match self.id_token()? {
+            Some(StoredIdToken { payload, .. }) => from_value(Value::Object(payload)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn is_authenticated(&self) -> Result<bool, Auth0Error> {
+        Ok(self.id_token()?.is_some())
+    }
+
+    pub async fn get_access_token(&self, options: Option<TokenOptions>) -> Result<String, Auth0Error> {
+        Ok(self.get_token_response(options).await?.access_token)
+    }
+
+    /// Returns the cached token if it is still valid, otherwise renews it with the refresh token or,
+    /// without one, with a `prompt=none` request in a hidden iframe.
+    pub async fn get_token_response(&self, options: Option<TokenOptions>) -> Result<TokenResponse, Auth0Error> {
+        let options = options.unwrap_or_default();
+        let audience = self.audience(options.audience.as_deref());
+        let scope = self.config.merged_scope(options.scope.as_deref());
+        let key = cache_key(&audience, &scope);
+
+        let cached: Option<CacheEntry> = self.store.get(&key);
+        if let Some(entry) = &cached {
+            if !options.ignore_cache && entry.expires_in() > EXPIRY_MARGIN {
+                return Ok(token_response(entry));
+            }
+        }
+
+        let timeout = options.timeout_in_seconds.unwrap_or_else(|| self.timeout());
+        let entry = match cached.and_then(|entry| entry.refresh_token) {
+            Some(refresh_token) => self.refresh(&audience, &scope, refresh_token).await?,
+            None => self.authorize_silently(audience, scope, timeout).await?,
+        };
+
+        Ok(token_response(&entry))
+    }
+
+    pub async fn id_token_claims<T: DeserializeOwned>(&self) -> Result<Option<IdToken<T>>, Auth0Error> {
+        let StoredIdToken { raw, mut payload } = match self.id_token()? {
+            Some(id_token) => id_token,
+            None => return Ok(None),
+        };
+        payload.insert("__raw".to_string(), Value::String(raw));
+
+        from_value(Value::Object(payload)).map(Some)
+    }
+
+    /// Clears the local session and, unless `local_only` is set, redirects to the tenant's `/v2/logout` endpoint.
+    pub fn logout(&self, options: &AuthLogoutOptions) -> Result<(), Auth0Error> {
+        self.store.clear();
+        if !options.local_only {
+            browser::window()?.location().assign(&self.build_logout_url(options))?;
+        }
+        Ok(())
+    }
+
+    async fn authorize_silently(&self, audience: String, scope: String, timeout: u32) -> Result<CacheEntry, Auth0Error> {
+        let transaction = self.transaction(audience, scope)?;
+        let url = self.authorize_url(&transaction, "web_message", Some("none"));
+
+        let document = browser::window()?
+            .document()
+            .ok_or_else(|| Auth0Error::Js("document is not available".to_string()))?;
+        let iframe: Element = document.create_element("iframe")?;
+        iframe.set_attribute("width", "0")?;
+        iframe.set_attribute("height", "0")?;
+        iframe.set_attribute("style", "display: none")?;
+        iframe.set_attribute("src", &url)?;
+        document
+            .body()
+            .ok_or_else(|| Auth0Error::Js("document has no body".to_string()))?
+            .append_child(&iframe)?;
+
+        let response = web_message::wait_for_response(self.origin(), None, timeout).await;
+        iframe.remove();
+
+        self.complete(&transaction, response?).await
+    }
+
+    async fn refresh(&self, audience: &str, scope: &str, refresh_token: String) -> Result<CacheEntry, Auth0Error> {
+        let body = RefreshExchange {
+            grant_type: "refresh_token",
+            client_id: &self.config.client_id,
+            refresh_token: &refresh_token,
+        };
+        let mut token: TokenEndpointResponse = browser::post_json(&self.url("/oauth/token"), &body).await?;
+        // Without refresh token rotation the old refresh token stays valid and is not returned again.
+        token.refresh_token = token.refresh_token.or(Some(refresh_token));
+
+        self.save(audience, scope, token)
+    }
+
+    async fn complete(&self, transaction: &Transaction, response: AuthorizationResponse) -> Result<CacheEntry, Auth0Error> {
+        if response.state.as_deref() != Some(transaction.state.as_str()) {
+            return Err(Auth0Error::InvalidState("Invalid state".to_string()));
+        }
+        let code = response
+            .code
+            .ok_or_else(|| Auth0Error::from_code("invalid_request", "Missing authorization code"))?;
+
+        let body = CodeExchange {
+            grant_type: "authorization_code",
+            client_id: &self.config.client_id,
+            code: &code,
+            code_verifier: &transaction.code_verifier,
+            redirect_uri: &transaction.redirect_uri,
+        };
+        let token: TokenEndpointResponse = browser::post_json(&self.url("/oauth/token"), &body).await?;
+
+        id_token::validate(&token.id_token, &self.config, &transaction.nonce, browser::now() / 1000.0)?;
+
+        self.save(&transaction.audience, &transaction.scope, token)
+    }
+
+    fn save(&self, audience: &str, scope: &str, token: TokenEndpointResponse) -> Result<CacheEntry, Auth0Error> {
+        let entry = CacheEntry {
+            access_token: token.access_token,
+            id_token: token.id_token,
+            refresh_token: token.refresh_token,
+            scope: token.scope,
+            expires_at: browser::now() / 1000.0 + token.expires_in,
+        };
+        self.store.set(&cache_key(audience, scope), &entry)?;
+        self.store.set(ID_TOKEN_KEY, &entry.id_token)?;
+
+        Ok(entry)
+    }
+
+    /// The stored ID token, `None` without one or once it has expired.
+    fn id_token(&self) -> Result<Option<StoredIdToken>, Auth0Error> {
+        let raw: String = match self.store.get(ID_TOKEN_KEY) {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+        let payload: Map<String, Value> = jwt::decode_claims(&raw)?;
+
+        Ok((!id_token::is_expired(&payload, &self.config)).then_some(StoredIdToken { raw, payload }))
+    }
+
+    fn transaction(&self, audience: String, scope: String) -> Result<Transaction, Auth0Error> {
+        let redirect_uri = match &self.config.redirect_uri {
+            Some(redirect_uri) => redirect_uri.clone(),
+            None => browser::window()?.location().origin()?,
+        };
+
+        Ok(Transaction {
+            state: browser::random_string(32)?,
+            nonce: browser::random_string(32)?,
+            code_verifier: browser::random_string(32)?,
+            redirect_uri,
+            audience,
+            scope,
+            app_state: None,
+        })
+    }
+
+    fn authorize_url(&self, transaction: &Transaction, response_mode: &str, prompt: Option<&str>) -> String {
+        let code_challenge = base64::encode_config(
+            Sha256::digest(transaction.code_verifier.as_bytes()),
+            base64::URL_SAFE_NO_PAD,
+        );
+
+        let mut params = vec![
+            ("client_id", self.config.client_id.clone()),
+            ("redirect_uri", transaction.redirect_uri.clone()),
+            ("scope", transaction.scope.clone()),
+            ("response_type", "code".to_string()),
+            ("response_mode", response_mode.to_string()),
+            ("state", transaction.state.clone()),
+            ("nonce", transaction.nonce.clone()),
+            ("code_challenge", code_challenge),
+            ("code_challenge_method", "S256".to_string()),
+        ];
+        if transaction.audience != DEFAULT_AUDIENCE {
+            params.push(("audience", transaction.audience.clone()));
+        }
+        if let Some(prompt) = prompt {
+            params.push(("prompt", prompt.to_string()));
+        }
+        if let Some(connection) = &self.config.connection {
+            params.push(("connection", connection.clone()));
+        }
+        if let Some(organization) = &self.config.organization {
+            params.push(("organization", organization.clone()));
+        }
+        if let Some(max_age) = self.config.max_age {
+            params.push(("max_age", max_age.to_string()));
+        }
+        for (key, value) in &self.config.authorize_params {
+            params.push((key.as_str(), value.clone()));
+        }
+
+        format!("{}/authorize?{}", self.origin(), query_string(&params))
+    }
+
+    fn audience(&self, audience: Option<&str>) -> String {
+        audience
+            .or(self.config.audience.as_deref())
+            .unwrap_or(DEFAULT_AUDIENCE)
+            .to_string()
+    }
+
+    fn timeout(&self) -> u32 {
+        self.config
+            .authorize_timeout_in_seconds
+            .unwrap_or(DEFAULT_TIMEOUT_IN_SECONDS)
+    }
+
+    fn origin(&self) -> String {
+        format!("https://{}", self.config.domain)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.origin(), path)
+    }
+}
+
+fn cache_key(audience: &str, scope: &str) -> String {
+    format!("{}::{}", audience, scope)
+}
+
+fn token_response(entry: &CacheEntry) -> TokenResponse {
+    TokenResponse {
+        access_token: entry.access_token.clone(),
+        id_token: entry.id_token.clone(),
+        expires_in: entry.expires_in().max(0.0) as u64,
+        scope: entry.scope.clone(),
+    }
+}
+
+fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Auth0Error> {
+    serde_json::from_value(value).map_err(|err| Auth0Error::Deserialize(err.to_string()))
+}
+
+fn query_string(params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn query_params(url: &str) -> HashMap<String, String> {
+    let url = url.split('#').next().unwrap_or_default();
+    let query = url.split_once('?').map(|(_, query)| query).unwrap_or_default();
+
+    query
+        .split('&')
+        .filter_map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = decode(parts.next()?)?;
+            let value = decode(parts.next().unwrap_or_default())?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// `encodeURIComponent`, written out so the URLs can be built without a JS engine.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                char::from(byte).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// `decodeURIComponent` of a form value, `None` for a malformed escape or invalid UTF-8.
+fn decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = std::str::from_utf8(rest.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &rest[2..];
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(audience: &str) -> Transaction {
+        Transaction {
+            state: "state".to_string(),
+            nonce: "nonce".to_string(),
+            // The example of RFC 7636, appendix B.
+            code_verifier: "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string(),
+            redirect_uri: "http://localhost:8000/callback?from=app".to_string(),
+            audience: audience.to_string(),
+            scope: "openid profile email".to_string(),
+            app_state: None,
+        }
+    }
+
+    #[test]
+    fn encodes_like_encode_uri_component() {
+        assert_eq!(encode("AZaz09-_.!~*'()"), "AZaz09-_.!~*'()");
+        assert_eq!(encode("a b&c=d/e?f#g+h"), "a%20b%26c%3Dd%2Fe%3Ff%23g%2Bh");
+        assert_eq!(encode("ü€"), "%C3%BC%E2%82%AC");
+    }
+
+    #[test]
+    fn decodes_form_values() {
+        assert_eq!(decode("a%20b+c%2Bd").as_deref(), Some("a b c+d"));
+        assert_eq!(decode("%C3%BC").as_deref(), Some("ü"));
+        assert_eq!(decode("%E2%82%AC%"), None);
+        assert_eq!(decode("%zz"), None);
+        assert_eq!(decode("%C3"), None);
+    }
+
+    #[test]
+    fn round_trips_the_query_string() {
+        let params = vec![
+            ("state", "a b&c".to_string()),
+            ("redirect_uri", "http://localhost:8000/?x=1".to_string()),
+        ];
+        let url = format!("https://tenant.eu.auth0.com/authorize?{}#fragment", query_string(&params));
+        let parsed = query_params(&url);
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed["state"], "a b&c");
+        assert_eq!(parsed["redirect_uri"], "http://localhost:8000/?x=1");
+    }
+
+    #[test]
+    fn reads_the_callback_parameters() {
+        let params = query_params(
+            "http://localhost:8000/?error=access_denied&error_description=User%20did%20not%20consent&state=xyz",
+        );
+
+        assert_eq!(params["error"], "access_denied");
+        assert_eq!(params["error_description"], "User did not consent");
+        assert_eq!(params["state"], "xyz");
+        assert!(!query_params("http://localhost:8000/").contains_key("code"));
+    }
+
+    #[test]
+    fn builds_the_authorize_url() {
+        let client = PkceClient::new(ConfigOptions::builder("tenant.eu.auth0.com", "client").build());
+        let url = client.authorize_url(&transaction(DEFAULT_AUDIENCE), "query", None);
+
+        assert!(url.starts_with("https://tenant.eu.auth0.com/authorize?"));
+        let params = query_params(&url);
+        assert_eq!(params["client_id"], "client");
+        assert_eq!(params["redirect_uri"], "http://localhost:8000/callback?from=app");
+        assert_eq!(params["scope"], "openid profile email");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["response_mode"], "query");
+        assert_eq!(params["state"], "state");
+        assert_eq!(params["nonce"], "nonce");
+        assert_eq!(params["code_challenge"], "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert!(!params.contains_key("audience"));
+        assert!(!params.contains_key("prompt"));
+    }
+
+    #[test]
+    fn adds_the_optional_authorize_parameters() {
+        let config = ConfigOptions::builder("tenant.eu.auth0.com", "client")
+            .connection("github")
+            .organization("org_1")
+            .max_age(300)
+            .authorize_param("ui_locales", "de")
+            .build();
+        let transaction = transaction("https://api.example.com");
+        let url = PkceClient::new(config).authorize_url(&transaction, "web_message", Some("none"));
+        let params = query_params(&url);
+
+        assert_eq!(params["audience"], "https://api.example.com");
+        assert_eq!(params["response_mode"], "web_message");
+        assert_eq!(params["prompt"], "none");
+        assert_eq!(params["connection"], "github");
+        assert_eq!(params["organization"], "org_1");
+        assert_eq!(params["max_age"], "300");
+        assert_eq!(params["ui_locales"], "de");
+    }
+
+    #[test]
+    fn builds_the_logout_url() {
+        let client = PkceClient::new(ConfigOptions::builder("tenant.eu.auth0.com", "client").build());
+
+        assert_eq!(
+            client.build_logout_url(&AuthLogoutOptions::return_to("http://localhost:8000/")),
+            "https://tenant.eu.auth0.com/v2/logout?client_id=client&returnTo=http%3A%2F%2Flocalhost%3A8000%2F"
+        );
+        let federated = AuthLogoutOptions {
+            federated: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            client.build_logout_url(&federated),
+            "https://tenant.eu.auth0.com/v2/logout?client_id=client&federated"
+        );
+    }
+}
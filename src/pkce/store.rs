@@ -0,0 +1,121 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_sys::Storage;
+
+use crate::{browser, Auth0Error};
+
+/// State of an in-flight `/authorize` request.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct Transaction {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub redirect_uri: String,
+    pub audience: String,
+    pub scope: String,
+    #[serde(default)]
+    pub app_state: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct CacheEntry {
+    pub access_token: String,
+    pub id_token: String,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub expires_at: f64,
+}
+
+impl CacheEntry {
+    pub fn expires_in(&self) -> f64 {
+        self.expires_at - browser::now() / 1000.0
+    }
+}
+
+/// Key/value store for transactions and tokens. Values are kept in memory and, when `persist` is set,
+/// mirrored to `localStorage` so they survive a reload.
+pub(crate) struct Store {
+    prefix: String,
+    persist: bool,
+    memory: RefCell<HashMap<String, String>>,
+}
+
+impl Store {
+    pub fn new(client_id: &str, persist: bool) -> Self {
+        Store {
+            prefix: format!("@@auth0spajs@@::{}", client_id),
+            persist,
+            memory: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let key = self.key(key);
+        let value = match self.memory.borrow().get(&key) {
+            Some(value) => Some(value.clone()),
+            None if self.persist => local_storage().and_then(|storage| storage.get_item(&key).ok().flatten()),
+            None => None,
+        }?;
+        serde_json::from_str(&value).ok()
+    }
+
+    pub fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<(), Auth0Error> {
+        let key = self.key(key);
+        let value = serde_json::to_string(value).map_err(|err| Auth0Error::Deserialize(err.to_string()))?;
+        if self.persist {
+            if let Some(storage) = local_storage() {
+                storage.set_item(&key, &value)?;
+            }
+        }
+        self.memory.borrow_mut().insert(key, value);
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        if let Some(storage) = local_storage() {
+            let keys: Vec<String> = (0..storage.length().unwrap_or(0))
+                .filter_map(|index| storage.key(index).ok().flatten())
+                .filter(|key| key.starts_with(&self.prefix))
+                .collect();
+            for key in keys {
+                let _ = storage.remove_item(&key);
+            }
+        }
+        self.memory.borrow_mut().clear();
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}::{}", self.prefix, key)
+    }
+}
+
+/// Redirect transactions have to survive the navigation to Auth0 and back, so they go to `sessionStorage`.
+pub(crate) fn save_transaction(client_id: &str, transaction: &Transaction) -> Result<(), Auth0Error> {
+    let value = serde_json::to_string(transaction).map_err(|err| Auth0Error::Deserialize(err.to_string()))?;
+    if let Some(storage) = session_storage() {
+        storage.set_item(&transaction_key(client_id), &value)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn take_transaction(client_id: &str) -> Option<Transaction> {
+    let storage = session_storage()?;
+    let key = transaction_key(client_id);
+    let value = storage.get_item(&key).ok().flatten()?;
+    let _ = storage.remove_item(&key);
+    serde_json::from_str(&value).ok()
+}
+
+fn transaction_key(client_id: &str) -> String {
+    format!("a0.spajs.txs.{}", client_id)
+}
+
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+fn session_storage() -> Option<Storage> {
+    web_sys::window()?.session_storage().ok().flatten()
+}
@@ -0,0 +1,70 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::Reflect;
+use serde::Deserialize;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{MessageEvent, Window};
+
+use crate::{browser, client::from_js, Auth0Error};
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct AuthorizationResponse {
+    pub code: Option<String>,
+    pub state: Option<String>,
+}
+
+/// Waits for the `authorization_response` message Auth0 posts back when `response_mode=web_message` is used,
+/// either from a hidden iframe or from a popup.
+pub(crate) async fn wait_for_response(
+    origin: String,
+    popup: Option<&Window>,
+    timeout_in_seconds: u32,
+) -> Result<AuthorizationResponse, Auth0Error> {
+    let window = browser::window()?;
+    let received: Rc<RefCell<Option<JsValue>>> = Rc::new(RefCell::new(None));
+
+    let listener = {
+        let received = received.clone();
+        Closure::wrap(Box::new(move |event: MessageEvent| {
+            if event.origin() != origin {
+                return;
+            }
+            let data = event.data();
+            let message_type = Reflect::get(&data, &JsValue::from_str("type")).ok().and_then(|value| value.as_string());
+            if message_type.as_deref() == Some("authorization_response") {
+                *received.borrow_mut() = Reflect::get(&data, &JsValue::from_str("response")).ok();
+            }
+        }) as Box<dyn FnMut(MessageEvent)>)
+    };
+    window.add_event_listener_with_callback("message", listener.as_ref().unchecked_ref())?;
+
+    let deadline = browser::now() + f64::from(timeout_in_seconds) * 1000.0;
+    let result = loop {
+        let response = received.borrow_mut().take();
+        if let Some(response) = response {
+            break parse_response(response);
+        }
+        if popup.map(|popup| popup.closed().unwrap_or(true)).unwrap_or(false) {
+            break Err(Auth0Error::PopupClosed("Popup closed".to_string()));
+        }
+        if browser::now() > deadline {
+            break Err(Auth0Error::Timeout("Timeout".to_string()));
+        }
+        if let Err(err) = browser::sleep(100).await {
+            break Err(err);
+        }
+    };
+
+    window.remove_event_listener_with_callback("message", listener.as_ref().unchecked_ref())?;
+    result
+}
+
+fn parse_response(response: JsValue) -> Result<AuthorizationResponse, Auth0Error> {
+    let has_error = Reflect::get(&response, &JsValue::from_str("error"))
+        .map(|error| error.is_truthy())
+        .unwrap_or(false);
+    if has_error {
+        return Err(response.into());
+    }
+    from_js(&response)
+}
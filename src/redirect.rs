@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::JsValue;
+use web_sys::UrlSearchParams;
+
+use crate::{browser, Auth0Error};
+
+const CALLBACK_PARAMS: [&str; 4] = ["code", "state", "error", "error_description"];
+
+thread_local! {
+    /// `state` of the callbacks this tab has processed, a callback is never exchanged twice.
+    static PROCESSED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether the current URL is an authorization callback (`state` with `code` or `error`) nobody has claimed yet.
+pub(crate) fn claim() -> Result<bool, Auth0Error> {
+    let params = UrlSearchParams::new_with_str(&browser::window()?.location().search()?)?;
+    let state = match params.get("state") {
+        Some(state) if params.has("code") || params.has("error") => state,
+        _ => return Ok(false),
+    };
+
+    Ok(PROCESSED.with(|processed| {
+        let mut processed = processed.borrow_mut();
+        let first = !processed.contains(&state);
+        if first {
+            processed.push(state);
+        }
+        first
+    }))
+}
+
+/// Removes the callback parameters from the address bar, so a reload does not replay the callback.
+pub(crate) fn strip() -> Result<(), Auth0Error> {
+    let window = browser::window()?;
+    let location = window.location();
+    let params = UrlSearchParams::new_with_str(&location.search()?)?;
+    for name in CALLBACK_PARAMS {
+        params.delete(name);
+    }
+
+    let search = String::from(params.to_string());
+    let url = match search.is_empty() {
+        true => format!("{}{}", location.pathname()?, location.hash()?),
+        false => format!("{}?{}{}", location.pathname()?, search, location.hash()?),
+    };
+    window.history()?.replace_state_with_url(&JsValue::NULL, "", Some(&url))?;
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::{
+    browser::{self, Wake},
+    jwt,
+    lease::Lease,
+    Auth0Error, Auth0Service, AuthChannel, AuthEvent, TokenOptions, TokenResponse,
+};
+
+/// A token this close to its refresh time is refreshed right away instead of being rescheduled.
+const DUE: Duration = Duration::from_secs(1);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RefreshOptions {
+    /// How long before the access token expires it is refreshed.
+    pub margin: Duration,
+    /// Delay before the first retry of a failed refresh. Doubled on every further failure.
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+    /// How long the tab refreshing a token may hold the refresh lease before another tab takes over.
+    /// Should cover a silent authorization, see `authorize_timeout_in_seconds`.
+    pub lease: Duration,
+    /// Audience and scope of the token to keep fresh.
+    pub token_options: Option<TokenOptions>,
+}
+
+impl Default for RefreshOptions {
+    fn default() -> Self {
+        RefreshOptions {
+            margin: Duration::from_secs(60),
+            min_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(300),
+            lease: Duration::from_secs(60),
+            token_options: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RefreshEvent {
+    /// A token was obtained. The next refresh is scheduled `margin` before it expires.
+    Refreshed(TokenResponse),
+    /// The refresh failed and is retried after `retry_in`.
+    Failed { error: Auth0Error, retry_in: Duration },
+    /// The session is gone and the user has to log in again. The scheduler stops.
+    LoginRequired(Auth0Error),
+}
+
+#[derive(Deserialize)]
+struct Expiry {
+    exp: Option<f64>,
+}
+
+/// Keeps the access token of an [`Auth0Service`] fresh by refreshing it silently shortly before it expires.
+/// Stops when dropped.
+///
+/// Across the tabs of an origin a refresh only happens under the refresh lease, and only if the shared cache does not
+/// already hold a token that another tab has just refreshed. A `TokenRefreshed` from another tab reschedules this
+/// one from the new token. That only keeps tabs from refreshing the same token twice if they share the token cache,
+/// e.g. with `CacheLocation::LocalStorage`; with an in-memory cache every tab still refreshes its own token.
+pub struct RefreshScheduler {
+    stopped: Rc<Cell<bool>>,
+    _channel: Option<AuthChannel>,
+}
+
+impl RefreshScheduler {
+    pub fn start(service: Auth0Service, options: RefreshOptions, on_event: impl Fn(RefreshEvent) + 'static) -> Self {
+        let stopped = Rc::new(Cell::new(false));
+        let wake = Wake::default();
+        let channel = {
+            let wake = wake.clone();
+            service
+                .on_auth_event(move |event| {
+                    if event == AuthEvent::TokenRefreshed {
+                        wake.wake();
+                    }
+                })
+                .ok()
+        };
+
+        let task_stopped = stopped.clone();
+        spawn_local(async move {
+            let lease = Lease::new(format!("auth0:{}:refresh", service.config().client_id()), options.lease);
+            let token_options = options.token_options.clone().unwrap_or_default();
+            let mut backoff = options.min_backoff;
+
+            while !task_stopped.get() {
+                let result = if lease.acquire().await {
+                    let result = refresh(&service, &token_options, options.margin).await;
+                    lease.release();
+                    result
+                } else {
+                    lease.wait_for_release().await;
+                    service.get_token_response(Some(from_cache(&token_options))).await
+                };
+
+                // Stopped while the request was in flight, nobody is listening any more.
+                if task_stopped.get() {
+                    break;
+                }
+                let delay = match result {
+                    Ok(response) => {
+                        backoff = options.min_backoff;
+                        let delay = refresh_delay(&response, options.margin);
+                        on_event(RefreshEvent::Refreshed(response));
+                        delay
+                    }
+                    Err(error) if error.requires_login() => {
+                        on_event(RefreshEvent::LoginRequired(error));
+                        break;
+                    }
+                    Err(error) => {
+                        let retry_in = backoff;
+                        backoff = (backoff * 2).min(options.max_backoff);
+                        on_event(RefreshEvent::Failed { error, retry_in });
+                        retry_in
+                    }
+                };
+
+                let millis = delay.as_millis().min(i32::MAX as u128) as i32;
+                if browser::sleep_until_woken(millis, &wake).await.is_err() {
+                    break;
+                }
+            }
+            task_stopped.set(true);
+        });
+
+        RefreshScheduler {
+            stopped,
+            _channel: channel,
+        }
+    }
+
+    pub fn stop(&self) {
+        self.stopped.set(true);
+    }
+
+    /// False once stopped, also when the scheduler stopped itself because the user has to log in again.
+    pub fn is_running(&self) -> bool {
+        !self.stopped.get()
+    }
+}
+
+impl Drop for RefreshScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The cached token, unless it is due. Only then a new one is requested and the other tabs are told about it.
+async fn refresh(
+    service: &Auth0Service,
+    token_options: &TokenOptions,
+    margin: Duration,
+) -> Result<TokenResponse, Auth0Error> {
+    if let Ok(cached) = service.get_token_response(Some(from_cache(token_options))).await {
+        if refresh_delay(&cached, margin) > DUE {
+            return Ok(cached);
+        }
+    }
+
+    let forced = TokenOptions {
+        ignore_cache: true,
+        ..token_options.clone()
+    };
+    let result = service.get_token_response(Some(forced)).await;
+    if result.is_ok() {
+        let _ = service.broadcast(AuthEvent::TokenRefreshed);
+    }
+    result
+}
+
+fn from_cache(token_options: &TokenOptions) -> TokenOptions {
+    TokenOptions {
+        ignore_cache: false,
+        ..token_options.clone()
+    }
+}
+
+/// Time until `margin` before the access token's `exp`, falling back to `expires_in` for opaque tokens.
+pub(crate) fn refresh_delay(response: &TokenResponse, margin: Duration) -> Duration {
+    let expires_in = match jwt::decode_claims::<Expiry>(&response.access_token) {
+        Ok(Expiry { exp: Some(exp) }) => exp - browser::now() / 1000.0,
+        _ => response.expires_in as f64,
+    };
+
+    Duration::from_secs_f64((expires_in - margin.as_secs_f64()).max(1.0))
+}
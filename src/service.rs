@@ -0,0 +1,266 @@
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    redirect, Auth0Client, Auth0Error, Auth0SpaJs, AuthChannel, AuthEvent, AuthLogoutOptions, ConfigOptions,
+    IdToken, RedirectLoginResult, TokenOptions, TokenResponse, User,
+};
+#[cfg(feature = "jwks")]
+use crate::JwksVerifier;
+#[cfg(feature = "pkce")]
+use crate::pkce::PkceClient;
+
+enum Backend {
+    Spa(Auth0Client),
+    #[cfg(feature = "pkce")]
+    Pkce(Box<PkceClient>),
+}
+
+struct ServiceInner {
+    backend: Backend,
+    config: ConfigOptions,
+    /// Posts session changes to the other tabs, `None` if the browser supports neither transport.
+    channel: Option<AuthChannel>,
+    #[cfg(feature = "jwks")]
+    verifier: JwksVerifier,
+}
+
+/// Handle to an `Auth0Client`. Cloning is cheap and every clone talks to the same client.
+#[derive(Clone)]
+pub struct Auth0Service(Rc<ServiceInner>);
+
+impl PartialEq for Auth0Service {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Auth0Service {
+    /// A service backed by `auth0-spa-js`, which has to be loaded first, see [`crate::load_auth0_spa_js`].
+    pub fn new(auth0: Auth0SpaJs, config: ConfigOptions) -> Self {
+        let backend = Backend::Spa(auth0.client((&config).into()));
+
+        Self::with_backend(backend, config)
+    }
+
+    /// A service backed by the Rust [`PkceClient`] instead of `auth0-spa-js`.
+    #[cfg(feature = "pkce")]
+    pub fn pkce(config: ConfigOptions) -> Self {
+        let backend = Backend::Pkce(Box::new(PkceClient::new(config.clone())));
+
+        Self::with_backend(backend, config)
+    }
+
+    fn with_backend(backend: Backend, config: ConfigOptions) -> Self {
+        Auth0Service(Rc::new(ServiceInner {
+            backend,
+            channel: AuthChannel::new(&channel_name(&config)).ok(),
+            #[cfg(feature = "jwks")]
+            verifier: JwksVerifier::new(&config),
+            config,
+        }))
+    }
+
+    /// The underlying `auth0-spa-js` client, `None` for a [`Auth0Service::pkce`] service.
+    pub fn client(&self) -> Option<&Auth0Client> {
+        match &self.0.backend {
+            Backend::Spa(client) => Some(client),
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(_) => None,
+        }
+    }
+
+    pub fn config(&self) -> &ConfigOptions {
+        &self.0.config
+    }
+
+    #[cfg(feature = "jwks")]
+    pub fn verifier(&self) -> &JwksVerifier {
+        &self.0.verifier
+    }
+
+    /// Tells the other tabs about a session change. Login, logout and token refreshes are posted automatically.
+    pub fn broadcast(&self, event: AuthEvent) -> Result<(), Auth0Error> {
+        match &self.0.channel {
+            Some(channel) => channel.post(event),
+            None => Ok(()),
+        }
+    }
+
+    /// Calls `on_event` for session changes in other tabs until the returned channel is dropped.
+    pub fn on_auth_event(&self, on_event: impl Fn(AuthEvent) + 'static) -> Result<AuthChannel, Auth0Error> {
+        AuthChannel::subscribe(&channel_name(self.config()), on_event)
+    }
+
+    pub async fn login_with_redirect(&self) -> Result<(), Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.login_with_redirect(None).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.login_with_redirect(None).await,
+        }
+    }
+
+    /// Like [`Auth0Service::login_with_redirect`], `app_state` is handed back by `handle_redirect_callback`, e.g. the
+    /// page to return to.
+    pub async fn login_with_app_state(&self, app_state: Value) -> Result<(), Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => {
+                let options = JsValue::from_serde(&json!({ "appState": app_state })).unwrap();
+                client.login_with_redirect(Some(options.into())).await
+            }
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.login_with_redirect(Some(app_state)).await,
+        }
+    }
+
+    /// Resolves once the popup has completed the login, or fails if it was blocked, closed or timed out.
+    pub async fn login_with_popup(&self) -> Result<(), Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.login_with_popup(None, None).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.login_with_popup().await,
+        }?;
+        let _ = self.broadcast(AuthEvent::Login);
+        Ok(())
+    }
+
+    pub async fn handle_redirect_callback(&self) -> Result<RedirectLoginResult, Auth0Error> {
+        let result = match &self.0.backend {
+            Backend::Spa(client) => client.handle_redirect_callback(None).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.handle_redirect_callback(None).await,
+        }?;
+        let _ = self.broadcast(AuthEvent::Login);
+        Ok(result)
+    }
+
+    /// Handles the redirect callback if Auth0 redirected back to the current URL, then removes `code`, `state` and
+    /// `error` from the address bar. `None` if the URL is no callback or this tab has already processed it.
+    pub async fn process_redirect_callback(&self) -> Option<Result<RedirectLoginResult, Auth0Error>> {
+        match redirect::claim() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(err) => return Some(Err(err)),
+        }
+
+        let result = self.handle_redirect_callback().await;
+        let stripped = redirect::strip();
+        Some(result.and_then(|result| stripped.map(|()| result)))
+    }
+
+    /// Restores a session Auth0 still has for the user but the cache does not, e.g. after a reload with the in-memory
+    /// cache. Like `auth0-spa-js`' `checkSession`, no session is not an error.
+    pub async fn check_session(&self) -> Result<(), Auth0Error> {
+        let result = match &self.0.backend {
+            Backend::Spa(client) => client.check_session(None).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.check_session().await,
+        };
+        match result {
+            Err(err) if err.requires_login() => Ok(()),
+            result => result,
+        }
+    }
+
+    pub async fn user(&self) -> Result<Option<User>, Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.get_user(None).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.user().await,
+        }
+    }
+
+    pub async fn is_authenticated(&self) -> Result<bool, Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.is_authenticated().await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.is_authenticated().await,
+        }
+    }
+
+    pub async fn get_access_token(&self, options: Option<TokenOptions>) -> Result<String, Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.get_token_silently(options.as_ref().map(Into::into)).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.get_access_token(options).await,
+        }
+    }
+
+    pub async fn get_token_response(&self, options: Option<TokenOptions>) -> Result<TokenResponse, Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.get_token_silently_detailed(options.as_ref().map(Into::into)).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.get_token_response(options).await,
+        }
+    }
+
+    pub async fn id_token_claims<T: DeserializeOwned>(&self) -> Result<Option<IdToken<T>>, Auth0Error> {
+        match &self.0.backend {
+            Backend::Spa(client) => client.get_id_token_claims(None).await,
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.id_token_claims().await,
+        }
+    }
+
+    /// Like [`Auth0Service::id_token_claims`], but fails unless the token's signature and claims check out.
+    #[cfg(feature = "jwks")]
+    pub async fn verified_id_token_claims<T: DeserializeOwned>(&self) -> Result<Option<IdToken<T>>, Auth0Error> {
+        match self.id_token_claims::<serde_json::Map<String, serde_json::Value>>().await? {
+            Some(claims) => self.verifier().verify_id_token(&claims.__raw, None).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets an access token and returns its claims once it has been verified for the requested audience,
+    /// falling back to the configured one.
+    #[cfg(feature = "jwks")]
+    pub async fn verified_access_token<C: DeserializeOwned>(&self, options: Option<TokenOptions>) -> Result<C, Auth0Error> {
+        let audience = options
+            .as_ref()
+            .and_then(|options| options.audience.clone())
+            .or_else(|| self.config().audience.clone())
+            .ok_or_else(|| Auth0Error::InvalidToken("An audience is required to verify an access token".to_string()))?;
+        let token = self.get_access_token(options).await?;
+
+        self.verifier().verify_access_token(&token, &audience).await
+    }
+
+    /// The URL `logout` would redirect to, e.g. to log out from a link.
+    pub fn build_logout_url(&self, options: Option<AuthLogoutOptions>) -> Result<String, Auth0Error> {
+        let options = self.logout_options(options);
+
+        match &self.0.backend {
+            Backend::Spa(client) => client.build_logout_url(Some((&options).into())),
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => Ok(client.build_logout_url(&options)),
+        }
+    }
+
+    /// Clears the session and redirects to Auth0 to end it there as well, unless `local_only` is set.
+    /// Other tabs are told about the logout either way.
+    pub fn logout(&self, options: Option<AuthLogoutOptions>) -> Result<(), Auth0Error> {
+        let options = self.logout_options(options);
+        let _ = self.broadcast(AuthEvent::Logout);
+
+        match &self.0.backend {
+            Backend::Spa(client) => client.logout(Some((&options).into())),
+            #[cfg(feature = "pkce")]
+            Backend::Pkce(client) => client.logout(&options),
+        }
+    }
+
+    fn logout_options(&self, options: Option<AuthLogoutOptions>) -> AuthLogoutOptions {
+        let mut options = options.unwrap_or_default();
+        if options.return_to.is_none() {
+            options.return_to = self.config().redirect_uri().map(str::to_string);
+        }
+        options
+    }
+}
+
+fn channel_name(config: &ConfigOptions) -> String {
+    format!("auth0:{}", config.client_id())
+}
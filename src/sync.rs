@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{BroadcastChannel, MessageEvent, Storage, StorageEvent};
+
+use crate::{browser, Auth0Error};
+
+thread_local! {
+    /// Identifies this tab, so it can ignore the messages it posted itself.
+    static TAB_ID: String = js_sys::Math::random().to_string();
+}
+
+pub(crate) fn tab_id() -> String {
+    TAB_ID.with(String::clone)
+}
+
+/// Session changes shared between the tabs of an origin. Tokens are never broadcast, tabs re-read them from
+/// their own cache.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthEvent {
+    Login,
+    Logout,
+    TokenRefreshed,
+    PermissionsChanged,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+    source: String,
+    event: AuthEvent,
+}
+
+enum Transport {
+    Broadcast(BroadcastChannel),
+    /// For browsers without `BroadcastChannel`: a write to `localStorage` fires a `storage` event in every other tab.
+    Storage(Storage),
+}
+
+/// A named channel to the other tabs of this origin. Stops listening when dropped.
+pub struct AuthChannel {
+    name: String,
+    transport: Transport,
+    listener: Option<Closure<dyn FnMut(JsValue)>>,
+}
+
+impl AuthChannel {
+    /// A channel that only posts.
+    pub fn new(name: &str) -> Result<Self, Auth0Error> {
+        let transport = match BroadcastChannel::new(name) {
+            Ok(channel) => Transport::Broadcast(channel),
+            Err(_) => Transport::Storage(
+                browser::window()?
+                    .local_storage()?
+                    .ok_or_else(|| Auth0Error::Js("localStorage is not available".to_string()))?,
+            ),
+        };
+
+        Ok(AuthChannel {
+            name: name.to_string(),
+            transport,
+            listener: None,
+        })
+    }
+
+    /// A channel that calls `on_event` for every event posted by another tab.
+    pub fn subscribe(name: &str, on_event: impl Fn(AuthEvent) + 'static) -> Result<Self, Auth0Error> {
+        let mut channel = AuthChannel::new(name)?;
+
+        let listener = match &channel.transport {
+            Transport::Broadcast(broadcast) => {
+                let listener = Closure::wrap(Box::new(move |event: JsValue| {
+                    if let Some(data) = event.unchecked_into::<MessageEvent>().data().as_string() {
+                        receive(&data, &on_event);
+                    }
+                }) as Box<dyn FnMut(JsValue)>);
+                broadcast.set_onmessage(Some(listener.as_ref().unchecked_ref()));
+                listener
+            }
+            Transport::Storage(_) => {
+                let key = channel.name.clone();
+                let listener = Closure::wrap(Box::new(move |event: JsValue| {
+                    let event: StorageEvent = event.unchecked_into();
+                    if event.key().as_deref() == Some(key.as_str()) {
+                        if let Some(data) = event.new_value() {
+                            receive(&data, &on_event);
+                        }
+                    }
+                }) as Box<dyn FnMut(JsValue)>);
+                browser::window()?.add_event_listener_with_callback("storage", listener.as_ref().unchecked_ref())?;
+                listener
+            }
+        };
+        channel.listener = Some(listener);
+
+        Ok(channel)
+    }
+
+    pub fn post(&self, event: AuthEvent) -> Result<(), Auth0Error> {
+        let message = Message {
+            source: tab_id(),
+            event,
+        };
+        let data = serde_json::to_string(&message).map_err(|err| Auth0Error::Deserialize(err.to_string()))?;
+
+        match &self.transport {
+            Transport::Broadcast(broadcast) => broadcast.post_message(&JsValue::from_str(&data))?,
+            Transport::Storage(storage) => {
+                storage.set_item(&self.name, &data)?;
+                storage.remove_item(&self.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AuthChannel {
+    fn drop(&mut self) {
+        match &self.transport {
+            Transport::Broadcast(broadcast) => {
+                broadcast.set_onmessage(None);
+                broadcast.close();
+            }
+            Transport::Storage(_) => {
+                if let (Some(listener), Some(window)) = (&self.listener, web_sys::window()) {
+                    let _ = window.remove_event_listener_with_callback("storage", listener.as_ref().unchecked_ref());
+                }
+            }
+        }
+    }
+}
+
+fn receive(data: &str, on_event: &impl Fn(AuthEvent)) {
+    if let Ok(message) = serde_json::from_str::<Message>(data) {
+        if TAB_ID.with(|id| *id != message.source) {
+            on_event(message.event);
+        }
+    }
+}
@@ -10,14 +10,16 @@ pub struct TestComponent {
 
 pub enum Msg {}
 
-#[derive(Properties, Clone, PartialEq, Default)]
-pub struct Props {}
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub service: Auth0Service,
+}
 
 impl Component for TestComponent {
     type Message = Msg;
     type Properties = Props;
-    fn create(_ctx: &Context<Self>) -> Self {
-        Auth0Service::login_with_popup();
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.props().service.callbacks().login_with_popup(Callback::noop());
 
         Self { }
     }